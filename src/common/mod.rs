@@ -1,8 +1,32 @@
-#[derive(Debug)]
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use rust_decimal::prelude::ToPrimitive;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum FormReport {
     Invalid,
     Quarly,
     Annually,
+    /// 10-Q/A
+    QuarlyAmendment,
+    /// 10-K/A
+    AnnuallyAmendment,
+    /// Annual report of a foreign private issuer
+    Annually20F,
+    /// 20-F/A
+    Annually20FAmendment,
+    /// Annual report of a Canadian issuer under MJDS
+    Annually40F,
+    /// 40-F/A
+    Annually40FAmendment,
+    /// Interim report of a foreign private issuer, semi-annual or quarterly
+    Interim6K,
+    /// 6-K/A
+    Interim6KAmendment,
 }
 
 impl From<&str> for FormReport {
@@ -10,17 +34,96 @@ impl From<&str> for FormReport {
         match s {
             "10-Q" => FormReport::Quarly,
             "10-K" => FormReport::Annually,
+            "10-Q/A" => FormReport::QuarlyAmendment,
+            "10-K/A" => FormReport::AnnuallyAmendment,
+            "20-F" => FormReport::Annually20F,
+            "20-F/A" => FormReport::Annually20FAmendment,
+            "40-F" => FormReport::Annually40F,
+            "40-F/A" => FormReport::Annually40FAmendment,
+            "6-K" => FormReport::Interim6K,
+            "6-K/A" => FormReport::Interim6KAmendment,
             _ => FormReport::Invalid,
         }
     }
 }
 
-#[derive(Debug)]
+impl FormReport {
+    /// Annual report, original or amended
+    pub fn is_annual(&self) -> bool {
+        matches!(
+            self,
+            FormReport::Annually
+                | FormReport::AnnuallyAmendment
+                | FormReport::Annually20F
+                | FormReport::Annually20FAmendment
+                | FormReport::Annually40F
+                | FormReport::Annually40FAmendment
+        )
+    }
+
+    /// Quarterly report, original or amended
+    pub fn is_quarterly(&self) -> bool {
+        matches!(self, FormReport::Quarly | FormReport::QuarlyAmendment)
+    }
+
+    /// Report covering part of a fiscal year: 10-Q or 6-K, original or amended
+    pub fn is_interim(&self) -> bool {
+        self.is_quarterly()
+            || matches!(self, FormReport::Interim6K | FormReport::Interim6KAmendment)
+    }
+
+    pub fn is_amendment(&self) -> bool {
+        matches!(
+            self,
+            FormReport::QuarlyAmendment
+                | FormReport::AnnuallyAmendment
+                | FormReport::Annually20FAmendment
+                | FormReport::Annually40FAmendment
+                | FormReport::Interim6KAmendment
+        )
+    }
+
+    /// SEC form type, empty for an invalid one
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FormReport::Invalid => "",
+            FormReport::Quarly => "10-Q",
+            FormReport::Annually => "10-K",
+            FormReport::QuarlyAmendment => "10-Q/A",
+            FormReport::AnnuallyAmendment => "10-K/A",
+            FormReport::Annually20F => "20-F",
+            FormReport::Annually20FAmendment => "20-F/A",
+            FormReport::Annually40F => "40-F",
+            FormReport::Annually40FAmendment => "40-F/A",
+            FormReport::Interim6K => "6-K",
+            FormReport::Interim6KAmendment => "6-K/A",
+        }
+    }
+}
+
+/// Serialized as the SEC form type, e.g. "10-K" or "20-F/A"
+impl Serialize for FormReport {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for FormReport {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let form = String::deserialize(deserializer)?;
+        Ok(FormReport::from(form.as_str()))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum FiscalPeriod {
     Invalid,
     Q1,
     Q2,
     Q3,
+    Q4,
+    H1,
+    H2,
     FY,
 }
 
@@ -30,36 +133,541 @@ impl From<&str> for FiscalPeriod {
             "Q1" => FiscalPeriod::Q1,
             "Q2" => FiscalPeriod::Q2,
             "Q3" => FiscalPeriod::Q3,
+            "Q4" => FiscalPeriod::Q4,
+            "H1" => FiscalPeriod::H1,
+            "H2" => FiscalPeriod::H2,
             "FY" => FiscalPeriod::FY,
             _ => FiscalPeriod::Invalid,
         }
     }
 }
 
-#[derive(Debug)]
+impl FiscalPeriod {
+    /// SEC fiscal period, empty for an invalid one
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FiscalPeriod::Invalid => "",
+            FiscalPeriod::Q1 => "Q1",
+            FiscalPeriod::Q2 => "Q2",
+            FiscalPeriod::Q3 => "Q3",
+            FiscalPeriod::Q4 => "Q4",
+            FiscalPeriod::H1 => "H1",
+            FiscalPeriod::H2 => "H2",
+            FiscalPeriod::FY => "FY",
+        }
+    }
+}
+
+/// Serialized as the SEC fiscal period, e.g. "Q1"
+impl Serialize for FiscalPeriod {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for FiscalPeriod {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let fp = String::deserialize(deserializer)?;
+        Ok(FiscalPeriod::from(fp.as_str()))
+    }
+}
+
+/// How to react to a fact that cannot be interpreted
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ParseMode {
+    /// Fail with `FundamentalError::MalformedFact`
+    #[default]
+    Strict,
+    /// Skip the fact and record a `ParseWarning`
+    Tolerant,
+}
+
+/// XBRL taxonomy a company reports its financial statements in, serialized as its key
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Taxonomy {
+    #[default]
+    #[serde(rename = "us-gaap")]
+    UsGaap,
+    /// IFRS, used by foreign private issuers
+    #[serde(rename = "ifrs-full")]
+    IfrsFull,
+    /// Document and entity information, e.g. shares outstanding on the cover page.
+    /// Never detected as the taxonomy of financial statements.
+    #[serde(rename = "dei")]
+    Dei,
+}
+
+impl Taxonomy {
+    /// Key of the taxonomy under `facts` in SEC companyfacts
+    pub fn key(&self) -> &'static str {
+        match self {
+            Taxonomy::UsGaap => "us-gaap",
+            Taxonomy::IfrsFull => "ifrs-full",
+            Taxonomy::Dei => "dei",
+        }
+    }
+
+    /// Taxonomy with the most concepts in companyfacts, `None` when none is present.
+    /// IFRS filers often report a handful of us-gaap concepts as well.
+    pub fn detect(json_data: &Value) -> Option<Taxonomy> {
+        [Taxonomy::UsGaap, Taxonomy::IfrsFull]
+            .into_iter()
+            .filter_map(|taxonomy| {
+                let concepts = json_data["facts"][taxonomy.key()].as_object()?;
+                Some((concepts.len(), taxonomy))
+            })
+            .max_by_key(|(count, taxonomy)| (*count, *taxonomy == Taxonomy::UsGaap))
+            .map(|(_, taxonomy)| taxonomy)
+    }
+}
+
+/// Which periods a statement history is made of
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HistoryMode {
+    /// One record per fiscal year, from 10-K, 20-F and 40-F filings
+    #[default]
+    Annual,
+    /// One record per fiscal quarter, from the three-month periods of 10-Q filings
+    /// with the missing ones derived from cumulative values
+    Quarterly,
+    /// One record per fiscal half-year, for issuers reporting interim results on 6-K
+    SemiAnnual,
+}
+
+/// Fact skipped while parsing in tolerant mode
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ParseWarning {
+    pub gaap_tag: String,
+    pub accn: Option<String>,
+    pub reason: String,
+}
+
+/// Kind of unit a concept is reported in
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UnitKind {
+    /// Amount in the reporting currency, e.g. "USD"
+    #[default]
+    Currency,
+    /// Amount per share in the reporting currency, e.g. "USD/shares"
+    PerShare,
+    /// Number of shares
+    Shares,
+}
+
+impl UnitKind {
+    /// Unit key under `units` in SEC companyfacts
+    pub fn unit(&self, currency: &str) -> String {
+        match self {
+            UnitKind::Currency => currency.to_owned(),
+            UnitKind::PerShare => format!("{}/shares", currency),
+            UnitKind::Shares => String::from("shares"),
+        }
+    }
+}
+
+/// Exact amount tagged with its currency, per share for per-share fields.
+/// The amount is serialized as a string so that no precision is lost.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Money {
+    pub amount: Decimal,
+    /// ISO 4217 code, e.g. "USD"
+    pub currency: String,
+}
+
+impl Money {
+    pub fn new(amount: impl Into<Decimal>, currency: impl Into<String>) -> Self {
+        Self {
+            amount: amount.into(),
+            currency: currency.into(),
+        }
+    }
+
+    /// Sum of both amounts, `None` when the currencies differ or on overflow
+    pub fn checked_add(&self, other: &Money) -> Option<Money> {
+        (self.currency == other.currency).then_some(())?;
+        Some(Money::new(
+            self.amount.checked_add(other.amount)?,
+            self.currency.clone(),
+        ))
+    }
+
+    /// Difference of both amounts, `None` when the currencies differ or on overflow
+    pub fn checked_sub(&self, other: &Money) -> Option<Money> {
+        (self.currency == other.currency).then_some(())?;
+        Some(Money::new(
+            self.amount.checked_sub(other.amount)?,
+            self.currency.clone(),
+        ))
+    }
+
+    /// Amount as a float, for ratios
+    pub fn to_f64(&self) -> f64 {
+        self.amount.to_f64().unwrap_or_default()
+    }
+}
+
+impl fmt::Display for Money {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.amount, self.currency)
+    }
+}
+
+/// Period a value is reported for, serialized as `{"start", "end"}` with a null start
+/// for an instant
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(from = "PeriodRecord", into = "PeriodRecord")]
+pub enum Period {
+    /// Point in time, e.g. a balance sheet date
+    Instant(NaiveDate),
+    /// Span of time, e.g. a fiscal quarter, both days included
+    Duration { start: NaiveDate, end: NaiveDate },
+}
+
+impl Period {
+    /// Duration when `start` is known, instant otherwise
+    pub fn new(start: Option<NaiveDate>, end: NaiveDate) -> Self {
+        match start {
+            Some(start) => Period::Duration { start, end },
+            None => Period::Instant(end),
+        }
+    }
+
+    /// First day of a duration, `None` for an instant
+    pub fn start(&self) -> Option<NaiveDate> {
+        match self {
+            Period::Instant(_) => None,
+            Period::Duration { start, .. } => Some(*start),
+        }
+    }
+
+    pub fn end(&self) -> NaiveDate {
+        match self {
+            Period::Instant(date) => *date,
+            Period::Duration { end, .. } => *end,
+        }
+    }
+
+    pub fn is_instant(&self) -> bool {
+        matches!(self, Period::Instant(_))
+    }
+
+    /// Days from start to end of a duration, `None` for an instant
+    pub fn days(&self) -> Option<i64> {
+        self.start().map(|start| (self.end() - start).num_days())
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct PeriodRecord {
+    start: Option<NaiveDate>,
+    end: NaiveDate,
+}
+
+impl From<PeriodRecord> for Period {
+    fn from(record: PeriodRecord) -> Self {
+        Period::new(record.start, record.end)
+    }
+}
+
+impl From<Period> for PeriodRecord {
+    fn from(period: Period) -> Self {
+        PeriodRecord {
+            start: period.start(),
+            end: period.end(),
+        }
+    }
+}
+
+/// Concept and filing a statement field was read from
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FieldSource {
+    pub concept: String,
+    pub unit: UnitKind,
+    pub accn: String,
+    /// Filing date of `accn`, `null` when the fact has none
+    #[serde(deserialize_with = "Option::deserialize")]
+    pub filed: Option<NaiveDate>,
+    pub period: Period,
+    /// Computed from cumulative values rather than reported as is
+    pub derived: bool,
+}
+
+/// Value of a statement period as reported by one filing
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Revision {
+    pub accn: String,
+    pub filed: Option<NaiveDate>,
+    pub form_report: FormReport,
+    pub value: Decimal,
+}
+
+/// Statement field that disagrees with the accounting identity it is part of
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ValidationIssue {
+    pub field: String,
+    /// Value computed from the other fields of the identity
+    pub expected: Decimal,
+    pub actual: Decimal,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(default)]
 pub struct MetaData {
-    #[allow(unused)]
+    /// Accession number of the filing the record period was read from
     pub accn: String,
-    pub start_date: String,
-    pub end_date: String,
-    #[allow(unused)]
-    pub filed_date: String,
+    /// Period of the record: that of its first duration field, or of its first instant
+    /// field when it has no duration, `None` until a field is read
+    pub period: Option<Period>,
+    pub filed: Option<NaiveDate>,
     #[allow(unused)]
+    #[serde(skip)]
     pub frame: String,
     pub form_report: FormReport,
     pub fiscal_period: FiscalPeriod,
+    /// Fiscal year of the period as labelled by the filer (`fy`), which differs from the
+    /// calendar year of the period end for off-calendar fiscal years
+    pub fiscal_year: Option<i32>,
+    /// Parse setting, serialized with the `StatementHistory` rather than every record
+    #[serde(skip)]
+    pub parse_mode: ParseMode,
+    /// Taxonomy to read, detected from the facts when `None` and recorded once parsed
+    pub taxonomy: Option<Taxonomy>,
+    /// Currency unit of the values: the reporting currency to read, detected from the
+    /// facts when `None`, or USD once normalized
+    pub currency: Option<String>,
+    /// Currency the company reported in, set once values were normalized to USD
+    pub reporting_currency: Option<String>,
+    /// Point-in-time cutoff on the filing date, `None` uses every fact
+    #[serde(skip)]
+    pub as_of: Option<NaiveDate>,
+    pub warnings: Vec<ParseWarning>,
+    /// Key: statement field, value: where its value came from
+    pub sources: BTreeMap<String, FieldSource>,
+    /// Key: statement field, value: every filing that reported the period, oldest first.
+    /// Only filled by the history parsers.
+    pub revisions: BTreeMap<String, Vec<Revision>>,
+    /// Mismatches found by the validation pass
+    pub validation_issues: Vec<ValidationIssue>,
 }
 
 impl Default for MetaData {
     fn default() -> Self {
         Self {
             accn: String::new(),
-            start_date: String::new(),
-            end_date: String::new(),
-            filed_date: String::new(),
+            period: None,
+            filed: None,
             frame: String::new(),
             form_report: FormReport::Quarly,
             fiscal_period: FiscalPeriod::Q1,
+            fiscal_year: None,
+            parse_mode: ParseMode::default(),
+            taxonomy: None,
+            currency: None,
+            reporting_currency: None,
+            as_of: None,
+            warnings: Vec::new(),
+            sources: BTreeMap::new(),
+            revisions: BTreeMap::new(),
+            validation_issues: Vec::new(),
         }
     }
 }
+
+impl MetaData {
+    /// Change from the first to the last reported value of a field, `None` if never restated
+    pub fn restatement(&self, field: &str) -> Option<Decimal> {
+        let revisions = self.revisions.get(field)?;
+        let first = revisions.first()?.value;
+        let last = revisions.last()?.value;
+        (first != last).then(|| last - first)
+    }
+}
+
+// --- Test ---
+#[cfg(test)]
+mod unittests {
+    use super::*;
+
+    #[test]
+    fn test_form_report() {
+        assert_eq!(FormReport::from("10-K/A"), FormReport::AnnuallyAmendment);
+        assert_eq!(FormReport::from("10-Q/A"), FormReport::QuarlyAmendment);
+        assert_eq!(FormReport::from("8-K"), FormReport::Invalid);
+        assert!(FormReport::AnnuallyAmendment.is_annual());
+        assert!(FormReport::AnnuallyAmendment.is_amendment());
+        assert!(!FormReport::Quarly.is_amendment());
+        assert!(FormReport::QuarlyAmendment.is_quarterly());
+        assert_eq!(FormReport::from("20-F"), FormReport::Annually20F);
+        assert!(FormReport::from("40-F").is_annual());
+        assert!(FormReport::from("6-K").is_interim());
+        assert!(!FormReport::Interim6K.is_quarterly());
+    }
+
+    #[test]
+    fn test_foreign_amendments() {
+        let amended_6k = FormReport::from("6-K/A");
+        assert_eq!(amended_6k, FormReport::Interim6KAmendment);
+        assert!(amended_6k.is_interim() && amended_6k.is_amendment());
+        assert!(!FormReport::Interim6K.is_amendment());
+
+        for (form, original) in [
+            ("20-F/A", FormReport::Annually20F),
+            ("40-F/A", FormReport::Annually40F),
+        ] {
+            let amended = FormReport::from(form);
+            assert_ne!(amended, FormReport::AnnuallyAmendment);
+            assert_ne!(amended, original);
+            assert!(amended.is_annual() && amended.is_amendment());
+            assert!(!original.is_amendment());
+        }
+    }
+
+    #[test]
+    fn test_form_report_round_trip() {
+        let forms = [
+            (FormReport::Invalid, ""),
+            (FormReport::Quarly, "10-Q"),
+            (FormReport::Annually, "10-K"),
+            (FormReport::QuarlyAmendment, "10-Q/A"),
+            (FormReport::AnnuallyAmendment, "10-K/A"),
+            (FormReport::Annually20F, "20-F"),
+            (FormReport::Annually20FAmendment, "20-F/A"),
+            (FormReport::Annually40F, "40-F"),
+            (FormReport::Annually40FAmendment, "40-F/A"),
+            (FormReport::Interim6K, "6-K"),
+            (FormReport::Interim6KAmendment, "6-K/A"),
+        ];
+        for (form, sec_form) in forms {
+            // Fails to compile when a variant is added without extending the list
+            match form {
+                FormReport::Invalid
+                | FormReport::Quarly
+                | FormReport::Annually
+                | FormReport::QuarlyAmendment
+                | FormReport::AnnuallyAmendment
+                | FormReport::Annually20F
+                | FormReport::Annually20FAmendment
+                | FormReport::Annually40F
+                | FormReport::Annually40FAmendment
+                | FormReport::Interim6K
+                | FormReport::Interim6KAmendment => {}
+            }
+            let json = serde_json::to_value(form).unwrap();
+            assert_eq!(json, serde_json::json!(sec_form));
+            assert_eq!(serde_json::from_value::<FormReport>(json).unwrap(), form);
+        }
+    }
+
+    #[test]
+    fn test_serialize_sec_strings() {
+        let json = serde_json::to_value((
+            FormReport::AnnuallyAmendment,
+            FiscalPeriod::Q4,
+            Taxonomy::IfrsFull,
+            HistoryMode::SemiAnnual,
+        ))
+        .unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!(["10-K/A", "Q4", "ifrs-full", "semi_annual"])
+        );
+
+        let form: FormReport = serde_json::from_value(serde_json::json!("20-F")).unwrap();
+        assert_eq!(form, FormReport::Annually20F);
+        let fp: FiscalPeriod = serde_json::from_value(serde_json::json!("")).unwrap();
+        assert_eq!(fp, FiscalPeriod::Invalid);
+    }
+
+    #[test]
+    fn test_serialize_period_and_money() {
+        let end = NaiveDate::from_ymd_opt(2024, 12, 31).unwrap();
+        let json =
+            serde_json::to_value((Period::Instant(end), Money::new(Decimal::new(68, 2), "USD")))
+                .unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!([
+                {"start": null, "end": "2024-12-31"},
+                {"amount": "0.68", "currency": "USD"},
+            ])
+        );
+
+        let period: Period =
+            serde_json::from_value(serde_json::json!({"start": "2024-10-01", "end": "2024-12-31"}))
+                .unwrap();
+        assert_eq!(period.days(), Some(91));
+    }
+
+    #[test]
+    fn test_field_source_requires_filed() {
+        let end = NaiveDate::from_ymd_opt(2024, 12, 31).unwrap();
+        let source = FieldSource {
+            concept: String::from("Revenues"),
+            unit: UnitKind::Currency,
+            accn: String::from("0001045810-25-000005"),
+            filed: None,
+            period: Period::Instant(end),
+            derived: false,
+        };
+        let mut json = serde_json::to_value(&source).unwrap();
+        assert_eq!(json["filed"], serde_json::Value::Null);
+        assert_eq!(
+            serde_json::from_value::<FieldSource>(json.clone()).unwrap(),
+            source
+        );
+        json.as_object_mut().unwrap().remove("filed");
+        assert!(serde_json::from_value::<FieldSource>(json).is_err());
+    }
+
+    #[test]
+    fn test_detect_taxonomy() {
+        let us_gaap = serde_json::json!({"facts": {"dei": {}, "us-gaap": {"Assets": {}}}});
+        assert_eq!(Taxonomy::detect(&us_gaap), Some(Taxonomy::UsGaap));
+
+        let ifrs = serde_json::json!({"facts": {
+            "us-gaap": {"Assets": {}},
+            "ifrs-full": {"Assets": {}, "Revenue": {}},
+        }});
+        assert_eq!(Taxonomy::detect(&ifrs), Some(Taxonomy::IfrsFull));
+        assert_eq!(Taxonomy::detect(&serde_json::json!({"facts": {}})), None);
+        let dei_only = serde_json::json!({"facts": {"dei": {"EntityPublicFloat": {}}}});
+        assert_eq!(Taxonomy::detect(&dei_only), None);
+    }
+
+    #[test]
+    fn test_unit_kind() {
+        assert_eq!(UnitKind::Currency.unit("EUR"), "EUR");
+        assert_eq!(UnitKind::PerShare.unit("USD"), "USD/shares");
+        assert_eq!(UnitKind::Shares.unit("USD"), "shares");
+    }
+
+    #[test]
+    fn test_period() {
+        let start = NaiveDate::from_ymd_opt(2024, 10, 1).unwrap();
+        let end = NaiveDate::from_ymd_opt(2024, 12, 31).unwrap();
+        let quarter = Period::new(Some(start), end);
+        assert_eq!(quarter, Period::Duration { start, end });
+        assert_eq!(quarter.days(), Some(91));
+        assert!(!quarter.is_instant());
+
+        let instant = Period::new(None, end);
+        assert_eq!(instant.start(), None);
+        assert_eq!(instant.end(), end);
+        assert_eq!(instant.days(), None);
+    }
+
+    #[test]
+    fn test_money() {
+        let eps = Money::new(Decimal::new(68, 2), "USD");
+        assert_eq!(eps.to_string(), "0.68 USD");
+        assert_eq!(eps.to_f64(), 0.68);
+        assert_eq!(
+            eps.checked_add(&Money::new(Decimal::new(32, 2), "USD")),
+            Some(Money::new(1, "USD"))
+        );
+        assert_eq!(eps.checked_sub(&Money::new(1, "EUR")), None);
+    }
+}
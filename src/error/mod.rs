@@ -0,0 +1,191 @@
+use chrono::NaiveDate;
+use std::fmt;
+use std::time::Duration;
+
+/// Errors returned by every public API of the crate
+#[derive(Debug)]
+pub enum FundamentalError {
+    /// Ticker is not listed in SEC's ticker-to-CIK table
+    TickerNotFound {
+        ticker: String,
+    },
+    /// Request failed or the server returned an error status
+    Http(reqwest::Error),
+    /// SEC kept answering 429 Too Many Requests after every retry
+    RateLimited {
+        url: String,
+        retry_after: Option<Duration>,
+    },
+    /// Company facts have no taxonomy section (e.g. `facts.us-gaap`)
+    MissingFacts,
+    /// Company does not report this concept
+    MissingGaapTag {
+        tag: String,
+    },
+    /// Concept is reported, but not in the requested unit
+    MissingUnit {
+        tag: String,
+        unit: String,
+    },
+    /// Fact is present but could not be interpreted
+    MalformedFact {
+        tag: String,
+        reason: String,
+    },
+    /// Not enough consecutive quarters in the history
+    InsufficientHistory {
+        needed: usize,
+        found: usize,
+    },
+    /// Quarter ending on `end` is not immediately followed by the next one
+    NonContiguousQuarters {
+        end: NaiveDate,
+        next_start: NaiveDate,
+    },
+    /// FX rate table has no rate for the currency on that date
+    MissingFxRate {
+        currency: String,
+        date: NaiveDate,
+    },
+    /// Response is not in the cache and the cache is offline only
+    NotCached {
+        url: String,
+    },
+    /// Bulk archive has no such entry, or the archive was not opened
+    MissingArchiveEntry {
+        archive: String,
+        name: String,
+    },
+    Xml(quick_xml::Error),
+    Json(serde_json::Error),
+    Io(std::io::Error),
+    Archive(zip::result::ZipError),
+    Csv(csv::Error),
+    Storage(rusqlite::Error),
+    #[cfg(feature = "parquet")]
+    Parquet(parquet::errors::ParquetError),
+}
+
+impl fmt::Display for FundamentalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FundamentalError::TickerNotFound { ticker } => {
+                write!(f, "Lookup error: could not find ticker {}", ticker)
+            }
+            FundamentalError::Http(err) => write!(f, "HTTP error: {}", err),
+            FundamentalError::RateLimited { url, retry_after } => match retry_after {
+                Some(wait) => write!(f, "Rate limited by SEC on {}, retry after {:?}", url, wait),
+                None => write!(f, "Rate limited by SEC on {}", url),
+            },
+            FundamentalError::MissingFacts => write!(f, "Parse error: could not find 'facts'"),
+            FundamentalError::MissingGaapTag { tag } => {
+                write!(f, "Parse error: could not find {}", tag)
+            }
+            FundamentalError::MissingUnit { tag, unit } => {
+                write!(f, "Parse error: could not find {} unit for {}", unit, tag)
+            }
+            FundamentalError::MalformedFact { tag, reason } => {
+                write!(f, "Parse error: malformed fact in {}: {}", tag, reason)
+            }
+            FundamentalError::InsufficientHistory { needed, found } => {
+                write!(
+                    f,
+                    "History error: need {} quarters, found {}",
+                    needed, found
+                )
+            }
+            FundamentalError::NonContiguousQuarters { end, next_start } => write!(
+                f,
+                "History error: quarter ending {} is followed by one starting {}",
+                end, next_start
+            ),
+            FundamentalError::MissingFxRate { currency, date } => {
+                write!(f, "FX error: no {} rate on {}", currency, date)
+            }
+            FundamentalError::NotCached { url } => {
+                write!(
+                    f,
+                    "Cache error: {} is not cached and offline mode is enabled",
+                    url
+                )
+            }
+            FundamentalError::MissingArchiveEntry { archive, name } => {
+                write!(f, "Archive error: {} has no entry {}", archive, name)
+            }
+            FundamentalError::Xml(err) => write!(f, "XML error: {}", err),
+            FundamentalError::Json(err) => write!(f, "JSON error: {}", err),
+            FundamentalError::Io(err) => write!(f, "I/O error: {}", err),
+            FundamentalError::Archive(err) => write!(f, "Archive error: {}", err),
+            FundamentalError::Csv(err) => write!(f, "CSV error: {}", err),
+            FundamentalError::Storage(err) => write!(f, "Storage error: {}", err),
+            #[cfg(feature = "parquet")]
+            FundamentalError::Parquet(err) => write!(f, "Parquet error: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for FundamentalError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            FundamentalError::Http(err) => Some(err),
+            FundamentalError::Xml(err) => Some(err),
+            FundamentalError::Json(err) => Some(err),
+            FundamentalError::Io(err) => Some(err),
+            FundamentalError::Archive(err) => Some(err),
+            FundamentalError::Csv(err) => Some(err),
+            FundamentalError::Storage(err) => Some(err),
+            #[cfg(feature = "parquet")]
+            FundamentalError::Parquet(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<reqwest::Error> for FundamentalError {
+    fn from(err: reqwest::Error) -> Self {
+        FundamentalError::Http(err)
+    }
+}
+
+impl From<quick_xml::Error> for FundamentalError {
+    fn from(err: quick_xml::Error) -> Self {
+        FundamentalError::Xml(err)
+    }
+}
+
+impl From<serde_json::Error> for FundamentalError {
+    fn from(err: serde_json::Error) -> Self {
+        FundamentalError::Json(err)
+    }
+}
+
+impl From<std::io::Error> for FundamentalError {
+    fn from(err: std::io::Error) -> Self {
+        FundamentalError::Io(err)
+    }
+}
+
+impl From<zip::result::ZipError> for FundamentalError {
+    fn from(err: zip::result::ZipError) -> Self {
+        FundamentalError::Archive(err)
+    }
+}
+
+impl From<csv::Error> for FundamentalError {
+    fn from(err: csv::Error) -> Self {
+        FundamentalError::Csv(err)
+    }
+}
+
+impl From<rusqlite::Error> for FundamentalError {
+    fn from(err: rusqlite::Error) -> Self {
+        FundamentalError::Storage(err)
+    }
+}
+
+#[cfg(feature = "parquet")]
+impl From<parquet::errors::ParquetError> for FundamentalError {
+    fn from(err: parquet::errors::ParquetError) -> Self {
+        FundamentalError::Parquet(err)
+    }
+}
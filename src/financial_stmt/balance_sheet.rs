@@ -1,57 +1,237 @@
-use crate::{common::MetaData, financial_stmt::FinancialStatement};
+use crate::{
+    common::{MetaData, Money, Taxonomy},
+    financial_stmt::{ConceptMapping, ConsistencyCheck, FinancialStatement},
+};
 
-#[derive(Debug)]
+use serde::{Deserialize, Serialize};
+
+/// Field-to-concept mapping, concepts are tried in order
+const US_GAAP_CONCEPTS: [ConceptMapping; 8] = [
+    ConceptMapping::new("total_assets", &["Assets"]),
+    ConceptMapping::new("current_assets", &["AssetsCurrent"]),
+    ConceptMapping::optional(
+        "noncurrent_assets",
+        &["AssetsNoncurrent", "NoncurrentAssets"],
+    ),
+    ConceptMapping::new(
+        "inventory",
+        &[
+            "InventoryNet",
+            "InventoryNetOfAllowancesCustomerAdvancesAndProgressBillings",
+            "InventoryGross",
+        ],
+    ),
+    ConceptMapping::new("total_liabilities", &["Liabilities"]),
+    ConceptMapping::new("current_liabilities", &["LiabilitiesCurrent"]),
+    ConceptMapping::new(
+        "total_equity",
+        &[
+            "StockholdersEquity",
+            "StockholdersEquityIncludingPortionAttributableToNoncontrollingInterest",
+        ],
+    ),
+    // Not tagged by every filer, the consistency check is skipped without it
+    ConceptMapping::optional(
+        "total_liabilities_and_equity",
+        &["LiabilitiesAndStockholdersEquity"],
+    ),
+];
+
+/// Field-to-concept mapping of IFRS filers
+const IFRS_CONCEPTS: [ConceptMapping; 8] = [
+    ConceptMapping::new("total_assets", &["Assets"]),
+    ConceptMapping::new("current_assets", &["CurrentAssets"]),
+    ConceptMapping::optional("noncurrent_assets", &["NoncurrentAssets"]),
+    ConceptMapping::new(
+        "inventory",
+        &["Inventories", "CurrentInventoriesHeldForSale"],
+    ),
+    ConceptMapping::new("total_liabilities", &["Liabilities"]),
+    ConceptMapping::new("current_liabilities", &["CurrentLiabilities"]),
+    ConceptMapping::new(
+        "total_equity",
+        &["EquityAttributableToOwnersOfParent", "Equity"],
+    ),
+    ConceptMapping::optional("total_liabilities_and_equity", &["EquityAndLiabilities"]),
+];
+
+/// Assets == LiabilitiesAndStockholdersEquity
+const CONSISTENCY_CHECKS: [ConsistencyCheck; 1] = [ConsistencyCheck::new(
+    "total_assets",
+    &[(1, "total_liabilities_and_equity")],
+)];
+
+#[derive(Debug, Default, Serialize, Deserialize)]
 pub struct BalanceSheet {
-    pub total_assets: i64,
-    pub current_assets: i64,
-    pub inventory: i64,
-    pub total_liabilities: i64,
-    pub current_liabilities: i64,
-    pub total_equity: i64,
-    pub sec_us_gaap: [&'static str; 6],
+    pub total_assets: Option<Money>,
+    pub current_assets: Option<Money>,
+    pub noncurrent_assets: Option<Money>,
+    pub inventory: Option<Money>,
+    pub total_liabilities: Option<Money>,
+    pub current_liabilities: Option<Money>,
+    pub total_equity: Option<Money>,
+    pub total_liabilities_and_equity: Option<Money>,
+    #[serde(rename = "metadata")]
     pub financial_facts: MetaData,
 }
 
-impl Default for BalanceSheet {
-    fn default() -> Self {
-        Self {
-            total_assets: 0,
-            current_assets: 0,
-            inventory: 0,
-            total_liabilities: 0,
-            current_liabilities: 0,
-            total_equity: 0,
-            sec_us_gaap: [
-                "LiabilitiesAndStockholdersEquity",
-                "AssetsCurrent",
-                "InventoryNet",
-                "Liabilities",
-                "LiabilitiesCurrent",
-                "StockholdersEquity",
-            ],
-            financial_facts: MetaData::default(),
+impl FinancialStatement for BalanceSheet {
+    const NAME: &'static str = "balance_sheet";
+
+    fn set_field_value(&mut self, field: &str, value: Money) {
+        match field {
+            "total_assets" => self.total_assets = Some(value),
+            "current_assets" => self.current_assets = Some(value),
+            "noncurrent_assets" => self.noncurrent_assets = Some(value),
+            "inventory" => self.inventory = Some(value),
+            "total_liabilities" => self.total_liabilities = Some(value),
+            "current_liabilities" => self.current_liabilities = Some(value),
+            "total_equity" => self.total_equity = Some(value),
+            "total_liabilities_and_equity" => self.total_liabilities_and_equity = Some(value),
+            _ => {}
         }
     }
-}
 
-impl FinancialStatement for BalanceSheet {
-    fn set_gaap_value(&mut self, gaap_tag: &str, value: i64) {
-        match gaap_tag {
-            "LiabilitiesAndStockholdersEquity" => self.total_assets = value,
-            "AssetsCurrent" => self.current_assets = value,
-            "InventoryNet" => self.inventory = value,
-            "Liabilities" => self.total_liabilities = value,
-            "LiabilitiesCurrent" => self.current_liabilities = value,
-            "StockholdersEquity" => self.total_equity = value,
-            _ => {}
+    fn get_field_value(&self, field: &str) -> Option<Money> {
+        match field {
+            "total_assets" => self.total_assets.clone(),
+            "current_assets" => self.current_assets.clone(),
+            "noncurrent_assets" => self.noncurrent_assets.clone(),
+            "inventory" => self.inventory.clone(),
+            "total_liabilities" => self.total_liabilities.clone(),
+            "current_liabilities" => self.current_liabilities.clone(),
+            "total_equity" => self.total_equity.clone(),
+            "total_liabilities_and_equity" => self.total_liabilities_and_equity.clone(),
+            _ => None,
         }
     }
 
-    fn get_gaap_tags(&self) -> &[&'static str] {
-        &self.sec_us_gaap
+    fn get_concept_mappings(&self, taxonomy: Taxonomy) -> &[ConceptMapping] {
+        match taxonomy {
+            Taxonomy::UsGaap => &US_GAAP_CONCEPTS,
+            Taxonomy::IfrsFull => &IFRS_CONCEPTS,
+            Taxonomy::Dei => &[],
+        }
+    }
+
+    fn get_consistency_checks(&self) -> &[ConsistencyCheck] {
+        &CONSISTENCY_CHECKS
     }
 
     fn get_metadata(&mut self) -> &mut MetaData {
         &mut self.financial_facts
     }
+
+    fn meta_data(&self) -> &MetaData {
+        &self.financial_facts
+    }
+}
+
+// --- Test ---
+#[cfg(test)]
+mod unittests {
+    use super::*;
+    use crate::common::FiscalPeriod;
+    use rust_decimal::Decimal;
+    use serde_json::{Value, json};
+
+    const FIXTURE: &str = include_str!("../../tests/fixtures/companyfacts_CIK0001045810.json");
+
+    fn usd(amount: i64) -> Option<Money> {
+        Some(Money::new(amount, "USD"))
+    }
+
+    #[test]
+    fn test_parse_quarly_latest_fixture() {
+        let json_data: Value = serde_json::from_str(FIXTURE).unwrap();
+        let mut balance_sheet = BalanceSheet::default();
+        balance_sheet.parse_quarly_latest(&json_data).unwrap();
+        assert_eq!(balance_sheet.total_assets, usd(1_160_000_000));
+        assert_eq!(balance_sheet.noncurrent_assets, usd(620_000_000));
+        let current_assets = balance_sheet.current_assets.as_ref().unwrap();
+        assert_eq!(
+            balance_sheet.total_assets,
+            current_assets.checked_add(balance_sheet.noncurrent_assets.as_ref().unwrap())
+        );
+
+        let sources = &balance_sheet.get_metadata().sources;
+        assert_eq!(sources["total_assets"].concept, "Assets");
+        assert_eq!(sources["noncurrent_assets"].concept, "AssetsNoncurrent");
+        assert!(balance_sheet.get_metadata().validation_issues.is_empty());
+    }
+
+    #[test]
+    fn test_quarterly_history_fixture() {
+        let json_data: Value = serde_json::from_str(FIXTURE).unwrap();
+        let mut balance_sheet = BalanceSheet::default();
+        let mut history = balance_sheet.parse_quarterly_history(&json_data).unwrap();
+        let ends: Vec<_> = history
+            .iter()
+            .filter_map(|record| record.financial_facts.period)
+            .map(|period| period.end().to_string())
+            .collect();
+        // Year end comparatives of the 10-Qs are skipped, the 10-K reports Q4
+        assert_eq!(
+            ends,
+            [
+                "2024-12-31",
+                "2024-09-30",
+                "2024-06-30",
+                "2024-03-31",
+                "2023-12-31",
+                "2023-09-30",
+                "2023-06-30",
+                "2023-03-31",
+                "2022-12-31",
+            ]
+        );
+        let q4 = &mut history[0];
+        assert_eq!(q4.total_assets, usd(1_160_000_000));
+        let meta_data = q4.get_metadata();
+        assert_eq!(meta_data.fiscal_period, FiscalPeriod::Q4);
+        assert!(!meta_data.sources["total_assets"].derived);
+    }
+
+    #[test]
+    fn test_liabilities_and_equity_is_optional() {
+        let fact = |val: i64| json!({"units": {"USD": [{"val": val, "form": "10-K", "fp": "FY", "fy": 2024, "end": "2024-12-31", "accn": "0001"}]}});
+        let json_data = json!({"facts": {"us-gaap": {
+            "Assets": fact(100),
+            "AssetsCurrent": fact(60),
+            "InventoryNet": fact(10),
+            "Liabilities": fact(50),
+            "LiabilitiesCurrent": fact(30),
+            "StockholdersEquity": fact(50),
+        }}});
+        // Strict mode, the default
+        let mut balance_sheet = BalanceSheet::default();
+        balance_sheet.parse_quarly_latest(&json_data).unwrap();
+        assert_eq!(balance_sheet.total_assets, usd(100));
+        assert_eq!(balance_sheet.total_liabilities_and_equity, None);
+        assert!(balance_sheet.get_metadata().validation_issues.is_empty());
+    }
+
+    #[test]
+    fn test_assets_mismatch_is_flagged() {
+        let fact = |val: i64| json!({"units": {"USD": [{"val": val, "form": "10-K", "fp": "FY", "fy": 2024, "end": "2024-12-31", "accn": "0001"}]}});
+        let json_data = json!({"facts": {"us-gaap": {
+            "Assets": fact(100),
+            "AssetsCurrent": fact(60),
+            "InventoryNet": fact(10),
+            "Liabilities": fact(50),
+            "LiabilitiesCurrent": fact(30),
+            "StockholdersEquity": fact(45),
+            "LiabilitiesAndStockholdersEquity": fact(95),
+        }}});
+        let mut balance_sheet = BalanceSheet::default();
+        // AssetsNoncurrent is optional and not reported here
+        balance_sheet.parse_quarly_latest(&json_data).unwrap();
+        assert_eq!(balance_sheet.noncurrent_assets, None);
+
+        let issues = &balance_sheet.get_metadata().validation_issues;
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].field, "total_assets");
+        assert_eq!(issues[0].expected, Decimal::from(95));
+        assert_eq!(issues[0].actual, Decimal::from(100));
+    }
 }
@@ -0,0 +1,211 @@
+use crate::error::FundamentalError;
+
+use log::{debug, warn};
+use serde_json::Value;
+use std::fs::File;
+use std::io::{BufReader, Read, Seek};
+use std::path::Path;
+use zip::{ZipArchive, result::ZipError};
+
+const COMPANY_FACTS_ARCHIVE: &str = "companyfacts.zip";
+const SUBMISSIONS_ARCHIVE: &str = "submissions.zip";
+
+/// Offline source reading SEC nightly bulk archives
+/// (`companyfacts.zip` and `submissions.zip`) without unpacking them to disk.
+/// Every company is stored as `CIK##########.json` inside the archive.
+pub struct BulkArchiveSource<R: Read + Seek = File> {
+    company_facts: ZipArchive<R>,
+    submissions: Option<ZipArchive<R>>,
+}
+
+impl BulkArchiveSource<File> {
+    pub fn open(
+        company_facts_path: impl AsRef<Path>,
+        submissions_path: Option<impl AsRef<Path>>,
+    ) -> Result<Self, FundamentalError> {
+        debug!(
+            "Opening bulk archive {}",
+            company_facts_path.as_ref().display()
+        );
+        let company_facts = ZipArchive::new(File::open(company_facts_path)?)?;
+        let submissions = match submissions_path {
+            Some(path) => Some(ZipArchive::new(File::open(path)?)?),
+            None => None,
+        };
+        Ok(Self {
+            company_facts,
+            submissions,
+        })
+    }
+}
+
+impl<R: Read + Seek> BulkArchiveSource<R> {
+    pub fn new(company_facts: ZipArchive<R>, submissions: Option<ZipArchive<R>>) -> Self {
+        Self {
+            company_facts,
+            submissions,
+        }
+    }
+
+    /// Company facts of one company, same shape as `SecClient::fetch_data`
+    pub fn company_facts(&mut self, cik: u32) -> Result<Value, FundamentalError> {
+        Self::read_json(
+            &mut self.company_facts,
+            COMPANY_FACTS_ARCHIVE,
+            &Self::entry_name(cik),
+        )
+    }
+
+    /// Submissions (filing history and company metadata) of one company
+    pub fn submissions(&mut self, cik: u32) -> Result<Value, FundamentalError> {
+        let name = Self::entry_name(cik);
+        let Some(archive) = self.submissions.as_mut() else {
+            return Err(FundamentalError::MissingArchiveEntry {
+                archive: String::from(SUBMISSIONS_ARCHIVE),
+                name,
+            });
+        };
+        Self::read_json(archive, SUBMISSIONS_ARCHIVE, &name)
+    }
+
+    /// CIKs of every company in `companyfacts.zip`
+    pub fn ciks(&self) -> Vec<u32> {
+        Self::archive_ciks(&self.company_facts)
+    }
+
+    /// CIKs of every company in `submissions.zip`
+    pub fn submission_ciks(&self) -> Vec<u32> {
+        self.submissions
+            .as_ref()
+            .map(Self::archive_ciks)
+            .unwrap_or_default()
+    }
+
+    /// Walk the whole universe, one company at a time.
+    /// Entries that fail to decode are logged and skipped.
+    pub fn for_each_company_facts<F>(&mut self, mut f: F)
+    where
+        F: FnMut(u32, Value),
+    {
+        for cik in self.ciks() {
+            match self.company_facts(cik) {
+                Ok(json) => f(cik, json),
+                Err(err) => warn!("Skipping CIK {}: {}", cik, err),
+            }
+        }
+    }
+
+    fn read_json(
+        archive: &mut ZipArchive<R>,
+        archive_name: &str,
+        name: &str,
+    ) -> Result<Value, FundamentalError> {
+        let file = archive.by_name(name).map_err(|err| match err {
+            ZipError::FileNotFound => FundamentalError::MissingArchiveEntry {
+                archive: archive_name.to_owned(),
+                name: name.to_owned(),
+            },
+            err => err.into(),
+        })?;
+        // serde_json reads one byte at a time, which must not each go through the decompressor
+        Ok(serde_json::from_reader(BufReader::new(file))?)
+    }
+
+    fn archive_ciks(archive: &ZipArchive<R>) -> Vec<u32> {
+        archive
+            .file_names()
+            .filter_map(Self::parse_entry_name)
+            .collect()
+    }
+
+    fn entry_name(cik: u32) -> String {
+        format!("CIK{:010}.json", cik)
+    }
+
+    /// `CIK0000320193.json` -> 320193, supplementary files such as
+    /// `CIK0000320193-submissions-001.json` are ignored
+    fn parse_entry_name(name: &str) -> Option<u32> {
+        name.strip_prefix("CIK")?
+            .strip_suffix(".json")?
+            .parse::<u32>()
+            .ok()
+    }
+}
+
+// --- Test ---
+#[cfg(test)]
+mod unittests {
+    use super::*;
+    use serde_json::json;
+    use std::io::{Cursor, Write};
+    use zip::write::{SimpleFileOptions, ZipWriter};
+
+    fn create_archive(entries: &[(&str, Value)]) -> ZipArchive<Cursor<Vec<u8>>> {
+        let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+        for (name, json) in entries {
+            writer
+                .start_file(*name, SimpleFileOptions::default())
+                .unwrap();
+            writer.write_all(json.to_string().as_bytes()).unwrap();
+        }
+        ZipArchive::new(writer.finish().unwrap()).unwrap()
+    }
+
+    fn create_source() -> BulkArchiveSource<Cursor<Vec<u8>>> {
+        let facts = create_archive(&[
+            (
+                "CIK0001045810.json",
+                json!({"cik": 1045810, "entityName": "NVIDIA CORP", "facts": {"us-gaap": {}}}),
+            ),
+            ("CIK0000320193.json", json!({"cik": 320193, "facts": {}})),
+        ]);
+        let submissions = create_archive(&[
+            (
+                "CIK0001045810.json",
+                json!({"cik": "1045810", "sic": "3674"}),
+            ),
+            ("CIK0001045810-submissions-001.json", json!({})),
+        ]);
+        BulkArchiveSource::new(facts, Some(submissions))
+    }
+
+    #[test]
+    fn test_company_facts() {
+        let mut source = create_source();
+        let json = source.company_facts(1045810).unwrap();
+        assert_eq!(json["entityName"], "NVIDIA CORP");
+        assert!(matches!(
+            source.company_facts(1),
+            Err(FundamentalError::MissingArchiveEntry { archive, name })
+                if archive == "companyfacts.zip" && name == "CIK0000000001.json"
+        ));
+    }
+
+    #[test]
+    fn test_submissions() {
+        let mut source = create_source();
+        assert_eq!(source.submissions(1045810).unwrap()["sic"], "3674");
+        assert_eq!(source.submission_ciks(), vec![1045810]);
+
+        let mut source = BulkArchiveSource::new(create_archive(&[]), None);
+        assert!(matches!(
+            source.submissions(1045810),
+            Err(FundamentalError::MissingArchiveEntry { archive, .. }) if archive == "submissions.zip"
+        ));
+    }
+
+    #[test]
+    fn test_for_each_company_facts() {
+        let mut source = create_source();
+        let mut ciks = source.ciks();
+        ciks.sort();
+        assert_eq!(ciks, vec![320193, 1045810]);
+
+        let mut visited = Vec::new();
+        source.for_each_company_facts(|cik, json| {
+            assert_eq!(json["cik"], cik);
+            visited.push(cik);
+        });
+        assert_eq!(visited.len(), 2);
+    }
+}
@@ -1,52 +1,166 @@
-use crate::common::MetaData;
-use crate::financial_stmt::FinancialStatement;
+use crate::common::{MetaData, Money, Taxonomy};
+use crate::financial_stmt::{ConceptMapping, FinancialStatement};
 
-#[derive(Debug)]
+use serde::{Deserialize, Serialize};
+
+/// Field-to-concept mapping, concepts are tried in order
+const US_GAAP_CONCEPTS: [ConceptMapping; 4] = [
+    ConceptMapping::new(
+        "operating_cash_flow",
+        &[
+            "NetCashProvidedByUsedInOperatingActivities",
+            "NetCashProvidedByUsedInOperatingActivitiesContinuingOperations",
+        ],
+    ),
+    ConceptMapping::new(
+        "investing_cash_flow",
+        &[
+            "NetCashProvidedByUsedInInvestingActivities",
+            "NetCashProvidedByUsedInInvestingActivitiesContinuingOperations",
+        ],
+    ),
+    ConceptMapping::new(
+        "financing_cash_flow",
+        &[
+            "NetCashProvidedByUsedInFinancingActivities",
+            "NetCashProvidedByUsedInFinancingActivitiesContinuingOperations",
+        ],
+    ),
+    ConceptMapping::new(
+        "end_cash_flow_position",
+        &[
+            "CashCashEquivalentsRestrictedCashAndRestrictedCashEquivalents",
+            "CashAndCashEquivalentsAtCarryingValue",
+            "Cash",
+        ],
+    ),
+];
+
+/// Field-to-concept mapping of IFRS filers
+const IFRS_CONCEPTS: [ConceptMapping; 4] = [
+    ConceptMapping::new(
+        "operating_cash_flow",
+        &["CashFlowsFromUsedInOperatingActivities"],
+    ),
+    ConceptMapping::new(
+        "investing_cash_flow",
+        &["CashFlowsFromUsedInInvestingActivities"],
+    ),
+    ConceptMapping::new(
+        "financing_cash_flow",
+        &["CashFlowsFromUsedInFinancingActivities"],
+    ),
+    ConceptMapping::new(
+        "end_cash_flow_position",
+        &["CashAndCashEquivalents", "Cash"],
+    ),
+];
+
+#[derive(Debug, Default, Serialize, Deserialize)]
 pub struct CashFlow {
-    pub operating_cash_flow: i64,
-    pub investing_cash_flow: i64,
-    pub financing_cash_flow: i64,
-    pub end_cash_flow_position: i64,
-    pub sec_us_gaap: [&'static str; 4],
+    pub operating_cash_flow: Option<Money>,
+    pub investing_cash_flow: Option<Money>,
+    pub financing_cash_flow: Option<Money>,
+    pub end_cash_flow_position: Option<Money>,
+    #[serde(rename = "metadata")]
     pub financial_facts: MetaData,
 }
 
-impl Default for CashFlow {
-    fn default() -> Self {
-        Self {
-            operating_cash_flow: 0,
-            investing_cash_flow: 0,
-            financing_cash_flow: 0,
-            end_cash_flow_position: 0,
-            sec_us_gaap: [
-                "NetCashProvidedByUsedInOperatingActivities",
-                "NetCashProvidedByUsedInInvestingActivities",
-                "NetCashProvidedByUsedInFinancingActivities",
-                "CashCashEquivalentsRestrictedCashAndRestrictedCashEquivalents",
-            ],
-            financial_facts: MetaData::default(),
+impl FinancialStatement for CashFlow {
+    const NAME: &'static str = "cash_flow";
+
+    fn set_field_value(&mut self, field: &str, value: Money) {
+        match field {
+            "operating_cash_flow" => self.operating_cash_flow = Some(value),
+            "investing_cash_flow" => self.investing_cash_flow = Some(value),
+            "financing_cash_flow" => self.financing_cash_flow = Some(value),
+            "end_cash_flow_position" => self.end_cash_flow_position = Some(value),
+            _ => {}
         }
     }
-}
 
-impl FinancialStatement for CashFlow {
-    fn set_gaap_value(&mut self, gaap_tag: &str, value: i64) {
-        match gaap_tag {
-            "NetCashProvidedByUsedInOperatingActivities" => self.operating_cash_flow = value,
-            "NetCashProvidedByUsedInInvestingActivities" => self.investing_cash_flow = value,
-            "NetCashProvidedByUsedInFinancingActivities" => self.financing_cash_flow = value,
-            "CashCashEquivalentsRestrictedCashAndRestrictedCashEquivalents" => {
-                self.end_cash_flow_position = value
-            }
-            _ => {}
+    fn get_field_value(&self, field: &str) -> Option<Money> {
+        match field {
+            "operating_cash_flow" => self.operating_cash_flow.clone(),
+            "investing_cash_flow" => self.investing_cash_flow.clone(),
+            "financing_cash_flow" => self.financing_cash_flow.clone(),
+            "end_cash_flow_position" => self.end_cash_flow_position.clone(),
+            _ => None,
         }
     }
 
-    fn get_gaap_tags(&self) -> &[&'static str] {
-        &self.sec_us_gaap
+    fn get_concept_mappings(&self, taxonomy: Taxonomy) -> &[ConceptMapping] {
+        match taxonomy {
+            Taxonomy::UsGaap => &US_GAAP_CONCEPTS,
+            Taxonomy::IfrsFull => &IFRS_CONCEPTS,
+            Taxonomy::Dei => &[],
+        }
     }
 
     fn get_metadata(&mut self) -> &mut MetaData {
         &mut self.financial_facts
     }
+
+    fn meta_data(&self) -> &MetaData {
+        &self.financial_facts
+    }
+}
+
+// --- Test ---
+#[cfg(test)]
+mod unittests {
+    use super::*;
+    use crate::common::FiscalPeriod;
+    use serde_json::Value;
+
+    const FIXTURE: &str = include_str!("../../tests/fixtures/companyfacts_CIK0001045810.json");
+
+    fn usd(amount: i64) -> Option<Money> {
+        Some(Money::new(amount, "USD"))
+    }
+
+    #[test]
+    fn test_quarterly_history_from_year_to_date() {
+        let json_data: Value = serde_json::from_str(FIXTURE).unwrap();
+        let mut cash_flow = CashFlow::default();
+        let mut history = cash_flow.parse_quarterly_history(&json_data).unwrap();
+        // 2024 operating cash flow is reported as 47.6M, 97.6M, 150M and 204.8M year to date
+        let quarters: Vec<_> = history
+            .iter_mut()
+            .take(4)
+            .map(|record| {
+                let derived = record.financial_facts.sources["operating_cash_flow"].derived;
+                (
+                    record.get_metadata().fiscal_period,
+                    record.operating_cash_flow.clone(),
+                    derived,
+                )
+            })
+            .collect();
+        assert_eq!(
+            quarters,
+            [
+                (FiscalPeriod::Q4, usd(54_800_000), true),
+                (FiscalPeriod::Q3, usd(52_400_000), true),
+                (FiscalPeriod::Q2, usd(50_000_000), true),
+                (FiscalPeriod::Q1, usd(47_600_000), false),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_record_period_is_duration() {
+        let json_data: Value = serde_json::from_str(FIXTURE).unwrap();
+        let mut cash_flow = CashFlow::default();
+        let quarterly = cash_flow.parse_quarterly_history(&json_data).unwrap();
+        let annual = cash_flow.parse_history(&json_data).unwrap();
+        for mut record in quarterly.into_iter().take(4).chain(annual) {
+            let source = record.financial_facts.sources["operating_cash_flow"].clone();
+            let meta_data = record.get_metadata();
+            // The cash position is an instant, read after the flows
+            assert_eq!(meta_data.period, Some(source.period));
+            assert!(meta_data.period.is_some_and(|period| !period.is_instant()));
+            assert_eq!(meta_data.accn, source.accn);
+        }
+    }
 }
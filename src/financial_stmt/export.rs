@@ -0,0 +1,281 @@
+use crate::error::FundamentalError;
+use crate::financial_stmt::{FinancialStatement, StatementHistory};
+
+use serde_json::{Map, Value};
+use std::io::Write;
+
+/// Columns describing the period of a record, first in both layouts
+const PERIOD_COLUMNS: [&str; 4] = ["period_start", "period_end", "fiscal_period", "form"];
+
+/// Columns following the period ones in the wide layout, fields come after them
+const WIDE_COLUMNS: [&str; 3] = ["accn", "filed", "currency"];
+
+/// Columns following the period ones in the long layout
+const LONG_COLUMNS: [&str; 7] = [
+    "field", "concept", "value", "unit", "accn", "filed", "derived",
+];
+
+/// Shape of an exported history
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum ExportLayout {
+    /// One row per period, one column per field
+    #[default]
+    Wide,
+    /// One row per period and reported field
+    Long,
+}
+
+/// Header and rows of an export, missing values are `None`
+type Table = (Vec<String>, Vec<Vec<Option<String>>>);
+
+impl<T: FinancialStatement> StatementHistory<T> {
+    /// Write the history as CSV, missing values left empty
+    pub fn write_csv(
+        &self,
+        writer: impl Write,
+        layout: ExportLayout,
+    ) -> Result<(), FundamentalError> {
+        let (header, rows) = self.export_table(layout);
+        let mut csv_writer = csv::Writer::from_writer(writer);
+        csv_writer.write_record(&header)?;
+        for row in rows {
+            csv_writer.write_record(row.iter().map(|value| value.as_deref().unwrap_or("")))?;
+        }
+        csv_writer.flush()?;
+        Ok(())
+    }
+
+    /// Write the history as a JSON array with one object per row, using the CSV column
+    /// names as keys. Values are strings so that amounts keep their precision.
+    pub fn write_json(
+        &self,
+        writer: impl Write,
+        layout: ExportLayout,
+    ) -> Result<(), FundamentalError> {
+        let (header, rows) = self.export_table(layout);
+        let objects: Vec<Map<String, Value>> = rows
+            .into_iter()
+            .map(|row| {
+                header
+                    .iter()
+                    .cloned()
+                    .zip(
+                        row.into_iter()
+                            .map(|value| value.map_or(Value::Null, Value::from)),
+                    )
+                    .collect()
+            })
+            .collect();
+        serde_json::to_writer_pretty(writer, &objects)?;
+        Ok(())
+    }
+
+    fn export_table(&self, layout: ExportLayout) -> Table {
+        // Same columns for every record, in the order of the mapping
+        let taxonomy = self
+            .taxonomy
+            .or_else(|| self.records.first()?.meta_data().taxonomy);
+        let fields: Vec<&'static str> = T::default()
+            .get_concept_mappings(taxonomy.unwrap_or_default())
+            .iter()
+            .map(|mapping| mapping.field)
+            .collect();
+
+        let mut header: Vec<String> = PERIOD_COLUMNS.iter().map(|c| c.to_string()).collect();
+        match layout {
+            ExportLayout::Wide => {
+                header.extend(WIDE_COLUMNS.iter().map(|c| c.to_string()));
+                header.extend(fields.iter().map(|field| field.to_string()));
+            }
+            ExportLayout::Long => header.extend(LONG_COLUMNS.iter().map(|c| c.to_string())),
+        }
+
+        let mut rows = Vec::new();
+        for record in &self.records {
+            let values: Vec<Option<String>> = fields
+                .iter()
+                .map(|field| {
+                    record
+                        .get_field_value(field)
+                        .map(|money| money.amount.to_string())
+                        .or_else(|| record.get_share_count(field).map(|count| count.to_string()))
+                })
+                .collect();
+            let meta_data = record.meta_data();
+            let period = vec![
+                meta_data
+                    .period
+                    .and_then(|period| period.start())
+                    .map(|start| start.to_string()),
+                meta_data.period.map(|period| period.end().to_string()),
+                Some(meta_data.fiscal_period.as_str().to_owned()),
+                Some(meta_data.form_report.as_str().to_owned()),
+            ];
+            let currency = meta_data.currency.clone();
+            match layout {
+                ExportLayout::Wide => {
+                    let mut row = period;
+                    row.push(Some(meta_data.accn.clone()));
+                    row.push(meta_data.filed.map(|filed| filed.to_string()));
+                    row.push(currency);
+                    row.extend(values);
+                    rows.push(row);
+                }
+                ExportLayout::Long => {
+                    for (field, value) in fields.iter().zip(values) {
+                        let Some(source) = meta_data.sources.get(*field) else {
+                            continue;
+                        };
+                        let unit = source.unit.unit(currency.as_deref().unwrap_or("USD"));
+                        // Period of the field itself, e.g. an instant cash position
+                        let mut row = period.clone();
+                        row[0] = source.period.start().map(|start| start.to_string());
+                        row[1] = Some(source.period.end().to_string());
+                        row.extend([
+                            Some(field.to_string()),
+                            Some(source.concept.clone()),
+                            value,
+                            Some(unit),
+                            Some(source.accn.clone()),
+                            source.filed.map(|filed| filed.to_string()),
+                            Some(source.derived.to_string()),
+                        ]);
+                        rows.push(row);
+                    }
+                }
+            }
+        }
+        (header, rows)
+    }
+}
+
+// --- Test ---
+#[cfg(test)]
+mod unittests {
+    use super::*;
+    use crate::common::HistoryMode;
+    use crate::financial_stmt::{
+        balance_sheet::BalanceSheet, cash_flow::CashFlow, income_statement::IncomeStatement,
+    };
+    use chrono::NaiveDate;
+
+    const FIXTURE: &str = include_str!("../../tests/fixtures/companyfacts_CIK0001045810.json");
+
+    fn quarterly_history<T: FinancialStatement>() -> StatementHistory<T> {
+        let json_data: Value = serde_json::from_str(FIXTURE).unwrap();
+        let mut history = StatementHistory::<T> {
+            mode: HistoryMode::Quarterly,
+            ..Default::default()
+        };
+        history.fill_history(&json_data).unwrap();
+        history
+    }
+
+    fn csv_lines<T: FinancialStatement>(layout: ExportLayout) -> Vec<String> {
+        let mut output = Vec::new();
+        quarterly_history::<T>()
+            .write_csv(&mut output, layout)
+            .unwrap();
+        String::from_utf8(output)
+            .unwrap()
+            .lines()
+            .map(str::to_owned)
+            .collect()
+    }
+
+    #[test]
+    fn test_write_csv_wide() {
+        let lines = csv_lines::<IncomeStatement>(ExportLayout::Wide);
+        assert_eq!(lines.len(), 10);
+        assert!(lines[0].starts_with(
+            "period_start,period_end,fiscal_period,form,accn,filed,currency,total_revenue,"
+        ));
+        assert!(lines[0].ends_with("weighted_average_shares_diluted"));
+        // Q4 is derived from the 10-K and has no per-share data
+        assert!(lines[1].starts_with("2024-10-01,2024-12-31,Q4,10-K,"));
+        assert!(lines[1].contains(",USD,170000000,"));
+        assert!(lines[1].ends_with(",,,,"));
+        assert!(lines[2].ends_with(",0.17,0.17,244000000,247000000"));
+
+        // Same leading columns for every statement
+        let lines = csv_lines::<BalanceSheet>(ExportLayout::Wide);
+        assert!(lines[0].starts_with(
+            "period_start,period_end,fiscal_period,form,accn,filed,currency,total_assets,"
+        ));
+        assert!(lines[1].starts_with(",2024-12-31,Q4,10-K,"));
+    }
+
+    #[test]
+    fn test_write_csv_long() {
+        let lines = csv_lines::<IncomeStatement>(ExportLayout::Long);
+        assert_eq!(
+            lines[0],
+            "period_start,period_end,fiscal_period,form,field,concept,value,unit,accn,filed,derived"
+        );
+        assert_eq!(
+            lines[1],
+            "2024-10-01,2024-12-31,Q4,10-K,total_revenue,Revenues,170000000,USD,0001045810-25-000005,2025-02-18,true"
+        );
+        assert!(lines.contains(&String::from(
+            "2024-07-01,2024-09-30,Q3,10-Q,eps_basic,EarningsPerShareBasic,0.17,USD/shares,0001045810-24-000028,2024-10-29,false"
+        )));
+        assert!(lines.iter().any(|line| line.contains(",244000000,shares,")));
+    }
+
+    #[test]
+    fn test_write_csv_long_filed_per_field() {
+        // EPS of Q3 as restated by the 10-K, the rest of the quarter from the 10-Q
+        let mut history = quarterly_history::<IncomeStatement>();
+        let source = history.records[1]
+            .get_metadata()
+            .sources
+            .get_mut("eps_basic")
+            .unwrap();
+        source.accn = String::from("0001045810-25-000005");
+        source.filed = NaiveDate::from_ymd_opt(2025, 2, 18);
+        let mut output = Vec::new();
+        history.write_csv(&mut output, ExportLayout::Long).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.contains(
+            "2024-07-01,2024-09-30,Q3,10-Q,eps_basic,EarningsPerShareBasic,0.17,USD/shares,0001045810-25-000005,2025-02-18,false"
+        ));
+        assert!(output.contains(
+            "2024-07-01,2024-09-30,Q3,10-Q,eps_diluted,EarningsPerShareDiluted,0.17,USD/shares,0001045810-24-000028,2024-10-29,false"
+        ));
+    }
+
+    #[test]
+    fn test_write_csv_cash_flow_periods() {
+        // Q1 as reported by the 10-Q, with the cash position read last
+        let lines = csv_lines::<CashFlow>(ExportLayout::Wide);
+        assert!(lines[4].starts_with("2024-01-01,2024-03-31,Q1,10-Q,0001045810-24-000012,"));
+        assert!(lines[1..].iter().all(|line| !line.starts_with(',')));
+
+        let lines = csv_lines::<CashFlow>(ExportLayout::Long);
+        let q1: Vec<_> = lines.iter().filter(|line| line.contains(",Q1,")).collect();
+        assert!(q1[0].starts_with("2024-01-01,2024-03-31,Q1,10-Q,operating_cash_flow,"));
+        assert!(q1[3].starts_with(",2024-03-31,Q1,10-Q,end_cash_flow_position,"));
+    }
+
+    #[test]
+    fn test_write_json() {
+        let mut output = Vec::new();
+        quarterly_history::<IncomeStatement>()
+            .write_json(&mut output, ExportLayout::Wide)
+            .unwrap();
+        let rows: Value = serde_json::from_slice(&output).unwrap();
+        assert_eq!(rows.as_array().unwrap().len(), 9);
+        assert_eq!(rows[0]["total_revenue"], "170000000");
+        assert_eq!(rows[0]["eps_basic"], Value::Null);
+        assert_eq!(rows[0]["fiscal_period"], "Q4");
+
+        let mut output = Vec::new();
+        quarterly_history::<IncomeStatement>()
+            .write_json(&mut output, ExportLayout::Long)
+            .unwrap();
+        let rows: Value = serde_json::from_slice(&output).unwrap();
+        assert_eq!(rows[0]["field"], "total_revenue");
+        assert_eq!(rows[0]["unit"], "USD");
+        assert_eq!(rows[0]["derived"], "true");
+    }
+}
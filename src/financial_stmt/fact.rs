@@ -0,0 +1,279 @@
+use crate::common::{FormReport, Period};
+
+use chrono::{Days, NaiveDate};
+use rust_decimal::Decimal;
+use serde_json::Value;
+use std::ops::RangeInclusive;
+use std::str::FromStr;
+
+/// Length in days of a discrete fiscal quarter, 13-week quarters included
+pub const QUARTER_DAYS: RangeInclusive<i64> = 80..=100;
+
+/// Length in days of a fiscal half-year, 26-week halves included
+pub const HALF_YEAR_DAYS: RangeInclusive<i64> = 170..=195;
+
+/// Length in days of a fiscal year, 52/53-week years included
+pub const YEAR_DAYS: RangeInclusive<i64> = 350..=380;
+
+/// One reported value of a concept, as found in SEC companyfacts
+/// `facts.<taxonomy>.<concept>.units.<unit>[]`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Fact {
+    /// Missing for instant (point-in-time) values such as balance sheet items
+    pub start: Option<NaiveDate>,
+    pub end: NaiveDate,
+    /// Value as reported, cents of per-share amounts included
+    pub val: Decimal,
+    pub accn: String,
+    /// Fiscal year and period of the filing, null for some facts
+    pub fy: Option<i32>,
+    pub fp: Option<String>,
+    pub form: String,
+    pub filed: Option<NaiveDate>,
+    pub frame: Option<String>,
+}
+
+impl Fact {
+    /// Parse a raw fact, returning the reason when it is malformed
+    pub fn from_value(value: &Value) -> Result<Self, String> {
+        let end = Self::required_date(value, "end")?;
+        let form = Self::required_str(value, "form")?;
+        let val = match &value["val"] {
+            // Parsed from the text so that 0.1 stays exactly 0.1
+            Value::Number(number) => {
+                let text = number.to_string();
+                Decimal::from_str(&text)
+                    .or_else(|_| Decimal::from_scientific(&text))
+                    .map_err(|_| format!("'val' is out of range: {}", number))?
+            }
+            Value::Null => return Err(String::from("missing 'val'")),
+            other => return Err(format!("'val' is not a number: {}", other)),
+        };
+        let start = match Self::optional_str(value, "start") {
+            Some(_) => Some(Self::required_date(value, "start")?),
+            None => None,
+        };
+        Ok(Self {
+            start,
+            end,
+            val,
+            accn: Self::optional_str(value, "accn").unwrap_or_default(),
+            fy: value["fy"].as_i64().and_then(|fy| i32::try_from(fy).ok()),
+            fp: Self::optional_str(value, "fp"),
+            form,
+            filed: Self::optional_str(value, "filed").and_then(|filed| Self::parse_date(&filed)),
+            frame: Self::optional_str(value, "frame"),
+        })
+    }
+
+    pub fn period(&self) -> Period {
+        Period::new(self.start, self.end)
+    }
+
+    /// Length in days of a duration fact, `None` for instants
+    pub fn duration_days(&self) -> Option<i64> {
+        self.period().days()
+    }
+
+    /// Whether this is a duration whose length falls in `days`
+    pub fn spans(&self, days: &RangeInclusive<i64>) -> bool {
+        self.duration_days()
+            .is_some_and(|length| days.contains(&length))
+    }
+
+    /// Whether this is a three-month duration rather than a year-to-date one
+    pub fn is_discrete_quarter(&self) -> bool {
+        self.spans(&QUARTER_DAYS)
+    }
+
+    /// Discrete last quarter of a cumulative duration, a full-year value yields Q4
+    pub fn derive_last_quarter(&self, earlier: &Fact) -> Option<Fact> {
+        self.derive_last_period(earlier, &QUARTER_DAYS, "Q4")
+    }
+
+    /// Discrete last period of a cumulative duration, i.e. this value minus the
+    /// cumulative value up to the previous period end (`earlier` must share the start).
+    /// An annual value yields `annual_fp`. `None` when the remainder does not span `days`.
+    pub fn derive_last_period(
+        &self,
+        earlier: &Fact,
+        days: &RangeInclusive<i64>,
+        annual_fp: &str,
+    ) -> Option<Fact> {
+        if self.start.is_none() || self.start != earlier.start {
+            return None;
+        }
+        if !days.contains(&(self.end - earlier.end).num_days()) {
+            return None;
+        }
+        let fp = match self.fp.as_deref() {
+            Some("FY") => Some(annual_fp.to_owned()),
+            _ if self.form_report().is_annual() => Some(annual_fp.to_owned()),
+            fp => fp.map(str::to_owned),
+        };
+        Some(Fact {
+            start: Some(earlier.end.checked_add_days(Days::new(1))?),
+            val: self.val.checked_sub(earlier.val)?,
+            fp,
+            frame: None,
+            ..self.clone()
+        })
+    }
+
+    pub fn form_report(&self) -> FormReport {
+        FormReport::from(self.form.as_str())
+    }
+
+    /// Whether both facts cover the same period
+    pub fn same_period(&self, other: &Fact) -> bool {
+        self.start == other.start && self.end == other.end
+    }
+
+    /// Fiscal year of the period of a fact from an annual report. The report labels its
+    /// comparative years with its own `fy`, so they are counted back from the latest
+    /// period the same filing reports in `facts`.
+    pub fn annual_fiscal_year(&self, facts: &[Fact]) -> Option<i32> {
+        let filing_end = facts
+            .iter()
+            .filter(|fact| fact.accn == self.accn)
+            .map(|fact| fact.end)
+            .max()?;
+        let years = ((filing_end - self.end).num_days() as f64 / 365.25).round() as i32;
+        Some(self.fy? - years)
+    }
+
+    /// Merge the facts one filing reports for the same period into a single fact holding
+    /// their sum, e.g. the shares outstanding of each class of stock. Order is kept.
+    pub fn sum_per_filing(facts: Vec<Fact>) -> Vec<Fact> {
+        let mut merged: Vec<Fact> = Vec::with_capacity(facts.len());
+        for fact in facts {
+            match merged
+                .iter_mut()
+                .find(|other| other.accn == fact.accn && other.same_period(&fact))
+            {
+                Some(other) => other.val += fact.val,
+                None => merged.push(fact),
+            }
+        }
+        merged
+    }
+
+    pub fn parse_date(value: &str) -> Option<NaiveDate> {
+        NaiveDate::parse_from_str(value, "%Y-%m-%d").ok()
+    }
+
+    fn required_date(value: &Value, key: &str) -> Result<NaiveDate, String> {
+        let text = Self::required_str(value, key)?;
+        Self::parse_date(&text).ok_or_else(|| format!("'{}' is not a date: {}", key, text))
+    }
+
+    fn required_str(value: &Value, key: &str) -> Result<String, String> {
+        Self::optional_str(value, key).ok_or_else(|| format!("missing '{}'", key))
+    }
+
+    fn optional_str(value: &Value, key: &str) -> Option<String> {
+        value[key].as_str().map(str::to_owned)
+    }
+}
+
+// --- Test ---
+#[cfg(test)]
+mod unittests {
+    use super::*;
+    use serde_json::json;
+
+    fn date(value: &str) -> Option<NaiveDate> {
+        Fact::parse_date(value)
+    }
+
+    #[test]
+    fn test_from_value() {
+        let value = json!({"start": "2024-01-01", "end": "2024-03-31", "val": 100, "accn": "0001", "fy": 2024, "fp": "Q1", "form": "10-Q", "filed": "2024-04-30", "frame": "CY2024Q1"});
+        let fact = Fact::from_value(&value).unwrap();
+        assert_eq!(fact.start, date("2024-01-01"));
+        assert_eq!(fact.val, Decimal::from(100));
+        assert_eq!(fact.filed, date("2024-04-30"));
+        assert_eq!(fact.fy, Some(2024));
+        assert_eq!(fact.fp.as_deref(), Some("Q1"));
+        assert_eq!(fact.duration_days(), Some(90));
+    }
+
+    #[test]
+    fn test_annual_fiscal_year() {
+        // Fiscal year ending in January, 52/53-week years
+        let fact = |end: &str| {
+            Fact::from_value(&json!({"end": end, "val": 1, "accn": "0001", "fy": 2025, "fp": "FY", "form": "10-K", "filed": "2025-03-19"})).unwrap()
+        };
+        let facts = [fact("2023-01-29"), fact("2024-01-28"), fact("2025-01-26")];
+        let years: Vec<_> = facts
+            .iter()
+            .map(|fact| fact.annual_fiscal_year(&facts))
+            .collect();
+        assert_eq!(years, [Some(2023), Some(2024), Some(2025)]);
+    }
+
+    #[test]
+    fn test_from_value_tolerates_nulls() {
+        let value =
+            json!({"end": "2024-03-31", "val": 1.5e3, "fy": null, "fp": null, "form": "10-Q"});
+        let fact = Fact::from_value(&value).unwrap();
+        assert_eq!(fact.val, Decimal::from(1500));
+        assert!(fact.filed.is_none());
+        assert!(fact.start.is_none());
+        assert!(fact.duration_days().is_none());
+        assert!(fact.fy.is_none());
+        assert!(fact.fp.is_none());
+
+        let eps = json!({"end": "2024-03-31", "val": 0.89, "form": "10-Q"});
+        assert_eq!(Fact::from_value(&eps).unwrap().val, Decimal::new(89, 2));
+    }
+
+    #[test]
+    fn test_from_value_malformed() {
+        assert_eq!(
+            Fact::from_value(&json!({"end": "2024-03-31", "val": 1})).unwrap_err(),
+            "missing 'form'"
+        );
+        assert_eq!(
+            Fact::from_value(&json!({"end": "2024-03-31", "form": "10-K"})).unwrap_err(),
+            "missing 'val'"
+        );
+        assert!(Fact::from_value(&json!({"end": "2024", "form": "10-K", "val": "1"})).is_err());
+        assert_eq!(
+            Fact::from_value(
+                &json!({"end": "2024-03-31", "start": "Q1", "form": "10-Q", "val": 1})
+            )
+            .unwrap_err(),
+            "'start' is not a date: Q1"
+        );
+        assert!(Fact::from_value(&json!([1, 2])).is_err());
+    }
+
+    #[test]
+    fn test_derive_last_quarter() {
+        let full_year = Fact::from_value(&json!({"start": "2024-01-01", "end": "2024-12-31", "val": 620, "fp": "FY", "form": "10-K"})).unwrap();
+        let nine_months = Fact::from_value(&json!({"start": "2024-01-01", "end": "2024-09-30", "val": 450, "fp": "Q3", "form": "10-Q"})).unwrap();
+        let six_months = Fact::from_value(&json!({"start": "2024-01-01", "end": "2024-06-30", "val": 290, "fp": "Q2", "form": "10-Q"})).unwrap();
+        assert!(!full_year.is_discrete_quarter());
+
+        let q4 = full_year.derive_last_quarter(&nine_months).unwrap();
+        assert_eq!(q4.start, date("2024-10-01"));
+        assert_eq!(Some(q4.end), date("2024-12-31"));
+        assert_eq!(q4.val, Decimal::from(170));
+        assert_eq!(q4.fp.as_deref(), Some("Q4"));
+        assert!(q4.is_discrete_quarter());
+
+        let q3 = nine_months.derive_last_quarter(&six_months).unwrap();
+        assert_eq!(q3.val, Decimal::from(160));
+        assert_eq!(q3.fp.as_deref(), Some("Q3"));
+
+        // Remainder spans two quarters
+        assert!(full_year.derive_last_quarter(&six_months).is_none());
+        let h2 = full_year
+            .derive_last_period(&six_months, &HALF_YEAR_DAYS, "H2")
+            .unwrap();
+        assert_eq!(h2.val, Decimal::from(330));
+        assert_eq!(h2.fp.as_deref(), Some("H2"));
+        assert!(six_months.spans(&HALF_YEAR_DAYS));
+    }
+}
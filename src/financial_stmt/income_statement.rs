@@ -1,58 +1,371 @@
-use crate::common::MetaData;
-use crate::financial_stmt::FinancialStatement;
+use crate::common::{MetaData, Money, Taxonomy, UnitKind};
+use crate::financial_stmt::{ConceptMapping, ConsistencyCheck, FinancialStatement};
 
-#[derive(Debug)]
+use serde::{Deserialize, Serialize};
+
+/// Field-to-concept mapping, concepts are tried in order
+const US_GAAP_CONCEPTS: [ConceptMapping; 10] = [
+    ConceptMapping::new(
+        "total_revenue",
+        &[
+            "Revenues",
+            "RevenueFromContractWithCustomerExcludingAssessedTax",
+            "RevenueFromContractWithCustomerIncludingAssessedTax",
+            "SalesRevenueNet",
+        ],
+    ),
+    ConceptMapping::new(
+        "cost_of_revenue",
+        &[
+            "CostOfRevenue",
+            "CostOfGoodsAndServicesSold",
+            "CostOfGoodsSold",
+            "CostOfServices",
+        ],
+    ),
+    ConceptMapping::new("gross_profit", &["GrossProfit"]),
+    ConceptMapping::new(
+        "operating_expense",
+        &["OperatingExpenses", "CostsAndExpenses"],
+    ),
+    ConceptMapping::new("operating_income", &["OperatingIncomeLoss"]),
+    ConceptMapping::new(
+        "net_income",
+        &[
+            "NetIncomeLoss",
+            "ProfitLoss",
+            "NetIncomeLossAvailableToCommonStockholdersBasic",
+        ],
+    ),
+    // Companies without listed common stock report no per-share data
+    ConceptMapping::optional("eps_basic", &["EarningsPerShareBasic"]).in_unit(UnitKind::PerShare),
+    ConceptMapping::optional(
+        "eps_diluted",
+        &["EarningsPerShareDiluted", "EarningsPerShareBasicAndDiluted"],
+    )
+    .in_unit(UnitKind::PerShare),
+    ConceptMapping::optional(
+        "weighted_average_shares_basic",
+        &["WeightedAverageNumberOfSharesOutstandingBasic"],
+    )
+    .in_unit(UnitKind::Shares),
+    ConceptMapping::optional(
+        "weighted_average_shares_diluted",
+        &[
+            "WeightedAverageNumberOfDilutedSharesOutstanding",
+            "WeightedAverageNumberOfShareOutstandingBasicAndDiluted",
+        ],
+    )
+    .in_unit(UnitKind::Shares),
+];
+
+/// Field-to-concept mapping of IFRS filers
+const IFRS_CONCEPTS: [ConceptMapping; 10] = [
+    ConceptMapping::new(
+        "total_revenue",
+        &["Revenue", "RevenueFromContractsWithCustomers"],
+    ),
+    ConceptMapping::new("cost_of_revenue", &["CostOfSales"]),
+    ConceptMapping::new("gross_profit", &["GrossProfit"]),
+    // IFRS has no total for expenses classified by function
+    ConceptMapping::optional("operating_expense", &["OperatingExpense"]),
+    ConceptMapping::new("operating_income", &["ProfitLossFromOperatingActivities"]),
+    ConceptMapping::new(
+        "net_income",
+        &["ProfitLoss", "ProfitLossAttributableToOwnersOfParent"],
+    ),
+    ConceptMapping::optional("eps_basic", &["BasicEarningsLossPerShare"])
+        .in_unit(UnitKind::PerShare),
+    ConceptMapping::optional("eps_diluted", &["DilutedEarningsLossPerShare"])
+        .in_unit(UnitKind::PerShare),
+    ConceptMapping::optional("weighted_average_shares_basic", &["WeightedAverageShares"])
+        .in_unit(UnitKind::Shares),
+    ConceptMapping::optional(
+        "weighted_average_shares_diluted",
+        &["AdjustedWeightedAverageShares"],
+    )
+    .in_unit(UnitKind::Shares),
+];
+
+/// GrossProfit == Revenues - CostOfRevenue
+const CONSISTENCY_CHECKS: [ConsistencyCheck; 1] = [ConsistencyCheck::new(
+    "gross_profit",
+    &[(1, "total_revenue"), (-1, "cost_of_revenue")],
+)];
+
+#[derive(Debug, Default, Serialize, Deserialize)]
 pub struct IncomeStatement {
-    pub total_revenue: i64,
-    pub cost_of_revenue: i64,
-    pub gross_profit: i64,
-    pub operating_expense: i64,
-    pub operating_income: i64,
-    pub net_income: i64,
-    pub sec_us_gaap: [&'static str; 6],
+    pub total_revenue: Option<Money>,
+    pub cost_of_revenue: Option<Money>,
+    pub gross_profit: Option<Money>,
+    pub operating_expense: Option<Money>,
+    pub operating_income: Option<Money>,
+    pub net_income: Option<Money>,
+    /// Earnings per share
+    pub eps_basic: Option<Money>,
+    pub eps_diluted: Option<Money>,
+    /// Weighted average number of shares outstanding over the period
+    pub weighted_average_shares_basic: Option<i64>,
+    pub weighted_average_shares_diluted: Option<i64>,
+    #[serde(rename = "metadata")]
     pub financial_facts: MetaData,
 }
 
-impl Default for IncomeStatement {
-    fn default() -> Self {
-        Self {
-            total_revenue: 0,
-            cost_of_revenue: 0,
-            gross_profit: 0,
-            operating_expense: 0,
-            operating_income: 0,
-            net_income: 0,
-            sec_us_gaap: [
-                "Revenues",
-                "CostOfRevenue",
-                "GrossProfit",
-                "OperatingExpenses",
-                "NonoperatingIncomeExpense",
-                "NetIncomeLoss",
-            ],
-            financial_facts: MetaData::default(),
+impl FinancialStatement for IncomeStatement {
+    const NAME: &'static str = "income_statement";
+
+    fn set_field_value(&mut self, field: &str, value: Money) {
+        match field {
+            "total_revenue" => self.total_revenue = Some(value),
+            "cost_of_revenue" => self.cost_of_revenue = Some(value),
+            "gross_profit" => self.gross_profit = Some(value),
+            "operating_expense" => self.operating_expense = Some(value),
+            "operating_income" => self.operating_income = Some(value),
+            "net_income" => self.net_income = Some(value),
+            "eps_basic" => self.eps_basic = Some(value),
+            "eps_diluted" => self.eps_diluted = Some(value),
+            _ => {}
         }
     }
-}
 
-impl FinancialStatement for IncomeStatement {
-    fn set_gaap_value(&mut self, gaap_tag: &str, value: i64) {
-        match gaap_tag {
-            "Revenues" => self.total_revenue = value,
-            "CostOfRevenue" => self.cost_of_revenue = value,
-            "GrossProfit" => self.gross_profit = value,
-            "OperatingExpenses" => self.operating_expense = value,
-            "NonoperatingIncomeExpense" => self.operating_income = value,
-            "NetIncomeLoss" => self.net_income = value,
+    fn get_field_value(&self, field: &str) -> Option<Money> {
+        match field {
+            "total_revenue" => self.total_revenue.clone(),
+            "cost_of_revenue" => self.cost_of_revenue.clone(),
+            "gross_profit" => self.gross_profit.clone(),
+            "operating_expense" => self.operating_expense.clone(),
+            "operating_income" => self.operating_income.clone(),
+            "net_income" => self.net_income.clone(),
+            "eps_basic" => self.eps_basic.clone(),
+            "eps_diluted" => self.eps_diluted.clone(),
+            _ => None,
+        }
+    }
+
+    fn set_share_count(&mut self, field: &str, value: i64) {
+        match field {
+            "weighted_average_shares_basic" => self.weighted_average_shares_basic = Some(value),
+            "weighted_average_shares_diluted" => self.weighted_average_shares_diluted = Some(value),
             _ => {}
         }
     }
 
-    fn get_gaap_tags(&self) -> &[&'static str] {
-        &self.sec_us_gaap
+    fn get_share_count(&self, field: &str) -> Option<i64> {
+        match field {
+            "weighted_average_shares_basic" => self.weighted_average_shares_basic,
+            "weighted_average_shares_diluted" => self.weighted_average_shares_diluted,
+            _ => None,
+        }
+    }
+
+    fn get_concept_mappings(&self, taxonomy: Taxonomy) -> &[ConceptMapping] {
+        match taxonomy {
+            Taxonomy::UsGaap => &US_GAAP_CONCEPTS,
+            Taxonomy::IfrsFull => &IFRS_CONCEPTS,
+            Taxonomy::Dei => &[],
+        }
+    }
+
+    fn get_consistency_checks(&self) -> &[ConsistencyCheck] {
+        &CONSISTENCY_CHECKS
     }
 
     fn get_metadata(&mut self) -> &mut MetaData {
         &mut self.financial_facts
     }
+
+    fn meta_data(&self) -> &MetaData {
+        &self.financial_facts
+    }
+}
+
+// --- Test ---
+#[cfg(test)]
+mod unittests {
+    use super::*;
+    use crate::common::{FiscalPeriod, FormReport, HistoryMode, Period};
+    use crate::financial_stmt::StatementHistory;
+    use chrono::NaiveDate;
+    use rust_decimal::Decimal;
+    use serde_json::{Value, json};
+
+    const FIXTURE: &str = include_str!("../../tests/fixtures/companyfacts_CIK0001045810.json");
+
+    fn fixture() -> Value {
+        serde_json::from_str(FIXTURE).unwrap()
+    }
+
+    fn usd(amount: impl Into<Decimal>) -> Option<Money> {
+        Some(Money::new(amount, "USD"))
+    }
+
+    #[test]
+    fn test_parse_quarly_latest_fixture() {
+        let mut income_stmt = IncomeStatement::default();
+        income_stmt.parse_quarly_latest(&fixture()).unwrap();
+        assert_eq!(income_stmt.total_revenue, usd(620_000_000));
+        assert_eq!(income_stmt.cost_of_revenue, usd(248_000_000));
+        assert_eq!(income_stmt.gross_profit, usd(372_000_000));
+        assert_eq!(income_stmt.operating_income, usd(186_000_000));
+
+        let sources = &income_stmt.get_metadata().sources;
+        assert_eq!(sources["operating_income"].concept, "OperatingIncomeLoss");
+        assert!(income_stmt.get_metadata().validation_issues.is_empty());
+    }
+
+    #[test]
+    fn test_history_fixture_is_consistent() {
+        let mut income_stmt = IncomeStatement::default();
+        let mut history = income_stmt.parse_history(&fixture()).unwrap();
+        assert_eq!(history.len(), 3);
+        for record in history.iter_mut() {
+            let total_revenue = record.total_revenue.as_ref().unwrap();
+            assert_eq!(
+                record.gross_profit,
+                total_revenue.checked_sub(record.cost_of_revenue.as_ref().unwrap())
+            );
+            assert!(record.get_metadata().validation_issues.is_empty());
+        }
+    }
+
+    #[test]
+    fn test_quarterly_history_fixture() {
+        let mut income_stmt = IncomeStatement::default();
+        let mut history = income_stmt.parse_quarterly_history(&fixture()).unwrap();
+        // 10-Qs of 2023 and 2024, Q4 derived from the 10-Ks of 2022 to 2024
+        assert_eq!(history.len(), 9);
+        let periods: Vec<_> = history
+            .iter_mut()
+            .map(|record| record.get_metadata().fiscal_period)
+            .collect();
+        use FiscalPeriod::*;
+        assert_eq!(periods, [Q4, Q3, Q2, Q1, Q4, Q3, Q2, Q1, Q4]);
+
+        // 620M for the year minus 450M for the first nine months
+        let q4 = &mut history[0];
+        assert_eq!(q4.total_revenue, usd(170_000_000));
+        assert_eq!(q4.gross_profit, usd(102_000_000));
+        let meta_data = q4.get_metadata();
+        assert_eq!(
+            meta_data.period,
+            Some(Period::Duration {
+                start: NaiveDate::from_ymd_opt(2024, 10, 1).unwrap(),
+                end: NaiveDate::from_ymd_opt(2024, 12, 31).unwrap(),
+            })
+        );
+        assert!(meta_data.sources["total_revenue"].derived);
+        assert!(meta_data.validation_issues.is_empty());
+
+        let q3 = &mut history[1];
+        assert_eq!(q3.total_revenue, usd(160_000_000));
+        let meta_data = q3.get_metadata();
+        let start = meta_data.period.and_then(|period| period.start());
+        assert_eq!(start, NaiveDate::from_ymd_opt(2024, 7, 1));
+        assert!(!meta_data.sources["total_revenue"].derived);
+    }
+
+    #[test]
+    fn test_per_share_fixture() {
+        let mut income_stmt = IncomeStatement::default();
+        income_stmt.parse_annually_latest(&fixture()).unwrap();
+        assert_eq!(income_stmt.eps_basic, usd(Decimal::new(68, 2)));
+        assert_eq!(income_stmt.eps_diluted, usd(Decimal::new(67, 2)));
+        assert_eq!(income_stmt.weighted_average_shares_basic, Some(244_000_000));
+        assert_eq!(
+            income_stmt.weighted_average_shares_diluted,
+            Some(247_000_000)
+        );
+        let sources = &income_stmt.get_metadata().sources;
+        assert_eq!(sources["eps_basic"].unit, UnitKind::PerShare);
+        assert_eq!(
+            sources["weighted_average_shares_basic"].unit,
+            UnitKind::Shares
+        );
+
+        // Averages are not derived from cumulative values, Q4 has no per-share data
+        let history = income_stmt.parse_quarterly_history(&fixture()).unwrap();
+        assert_eq!(history[1].eps_basic, usd(Decimal::new(17, 2)));
+        assert_eq!(
+            history[1].weighted_average_shares_diluted,
+            Some(247_000_000)
+        );
+        assert_eq!(history[0].eps_basic, None);
+        assert_eq!(history[0].weighted_average_shares_basic, None);
+    }
+
+    #[test]
+    fn test_serialize_history() {
+        let mut history = StatementHistory::<IncomeStatement> {
+            mode: HistoryMode::Quarterly,
+            ..Default::default()
+        };
+        history.fill_history(&fixture()).unwrap();
+        let json = serde_json::to_value(&history).unwrap();
+        assert_eq!(json["mode"], "quarterly");
+        let q4 = &json["records"][0];
+        assert_eq!(
+            q4["total_revenue"],
+            json!({"amount": "170000000", "currency": "USD"})
+        );
+        assert_eq!(q4["eps_basic"], Value::Null);
+        assert_eq!(q4["metadata"]["form_report"], "10-K");
+        assert_eq!(q4["metadata"]["fiscal_period"], "Q4");
+        assert_eq!(
+            q4["metadata"]["period"],
+            json!({"start": "2024-10-01", "end": "2024-12-31"})
+        );
+        assert_eq!(q4["metadata"]["sources"]["total_revenue"]["derived"], true);
+        // Parse settings are kept once, on the history
+        assert_eq!(json["parse_mode"], "strict");
+        for setting in ["parse_mode", "as_of", "frame"] {
+            assert!(q4["metadata"].get(setting).is_none());
+        }
+
+        let mut restored: StatementHistory<IncomeStatement> = serde_json::from_value(json).unwrap();
+        assert_eq!(restored.records.len(), 9);
+        assert_eq!(restored.records[1].eps_basic, usd(Decimal::new(17, 2)));
+        let meta_data = restored.records[0].get_metadata();
+        assert_eq!(meta_data.form_report, FormReport::Annually);
+        assert_eq!(meta_data.sources["total_revenue"].concept, "Revenues");
+    }
+
+    #[test]
+    fn test_gross_profit_mismatch_is_flagged() {
+        let fact = |val: i64| json!({"units": {"USD": [{"val": val, "form": "10-K", "fp": "FY", "fy": 2024, "start": "2024-01-01", "end": "2024-12-31", "accn": "0001"}]}});
+        let json_data = json!({"facts": {"us-gaap": {
+            "Revenues": fact(100),
+            "CostOfRevenue": fact(40),
+            "GrossProfit": fact(70),
+            "OperatingExpenses": fact(10),
+            "OperatingIncomeLoss": fact(60),
+            "NetIncomeLoss": fact(50),
+        }}});
+        let mut income_stmt = IncomeStatement::default();
+        income_stmt.parse_quarly_latest(&json_data).unwrap();
+        let issues = &income_stmt.get_metadata().validation_issues;
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].field, "gross_profit");
+        assert_eq!(issues[0].expected, Decimal::from(60));
+        assert_eq!(issues[0].actual, Decimal::from(70));
+    }
+
+    #[test]
+    fn test_parse_ifrs_filer() {
+        let fact = |val: i64| json!({"units": {"USD": [{"val": val, "form": "20-F", "fp": "FY", "fy": 2024, "start": "2024-01-01", "end": "2024-12-31", "accn": "0001"}]}});
+        let json_data = json!({"facts": {"ifrs-full": {
+            "Revenue": fact(100),
+            "CostOfSales": fact(40),
+            "GrossProfit": fact(60),
+            "ProfitLossFromOperatingActivities": fact(30),
+            "ProfitLoss": fact(20),
+        }}});
+        let mut income_stmt = IncomeStatement::default();
+        income_stmt.parse_annually_latest(&json_data).unwrap();
+        assert_eq!(income_stmt.total_revenue, usd(100));
+        assert_eq!(income_stmt.operating_income, usd(30));
+        assert_eq!(income_stmt.net_income, usd(20));
+        let meta_data = income_stmt.get_metadata();
+        assert_eq!(meta_data.taxonomy, Some(Taxonomy::IfrsFull));
+        assert!(meta_data.validation_issues.is_empty());
+    }
 }
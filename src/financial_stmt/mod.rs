@@ -1,135 +1,796 @@
 pub mod balance_sheet;
+pub mod bulk_archive;
 pub mod cash_flow;
+pub mod export;
+pub mod fact;
 pub mod income_statement;
 pub mod sec_client;
+pub mod share_data;
+pub mod ticker_directory;
+pub mod ttm;
 
-use crate::common::{FiscalPeriod, FormReport, MetaData};
+use crate::common::{
+    FieldSource, FiscalPeriod, FormReport, HistoryMode, MetaData, Money, ParseMode, ParseWarning,
+    Revision, Taxonomy, UnitKind, ValidationIssue,
+};
+use crate::error::FundamentalError;
+use crate::financial_stmt::fact::{Fact, HALF_YEAR_DAYS, QUARTER_DAYS, YEAR_DAYS};
+use crate::fx::{CurrencyConverter, RateBasis};
 
-use chrono::{Datelike, Utc};
+use chrono::{Datelike, NaiveDate, Utc};
+use log::warn;
+use rust_decimal::Decimal;
+use rust_decimal::prelude::ToPrimitive;
+use serde::{Deserialize, Serialize};
 use serde_json::{Map, Value};
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, HashMap};
 
+/// Serialized with every record and the settings it was parsed with
+#[derive(Debug, Serialize, Deserialize)]
 pub struct StatementHistory<T> {
     pub records: Vec<T>,
+    pub parse_mode: ParseMode,
+    pub mode: HistoryMode,
+    /// Taxonomy to read, detected from the facts when `None`
+    pub taxonomy: Option<Taxonomy>,
+    /// Currency unit to read, detected from the facts when `None`
+    pub currency: Option<String>,
+    /// Only use facts filed on or before this date
+    pub as_of: Option<NaiveDate>,
+    /// Facts skipped while parsing in tolerant mode, and fields left out of the trailing
+    /// twelve months
+    pub warnings: Vec<ParseWarning>,
 }
 
-impl<T: FinancialStatement> StatementHistory<T> {
-    pub fn default() -> Self {
+impl<T> Default for StatementHistory<T> {
+    fn default() -> Self {
         Self {
             records: Vec::new(),
+            parse_mode: ParseMode::default(),
+            mode: HistoryMode::default(),
+            taxonomy: None,
+            currency: None,
+            as_of: None,
+            warnings: Vec::new(),
         }
     }
-    pub fn fill_history(&mut self, json_data: &Value) -> Result<(), Box<dyn std::error::Error>> {
+}
+
+impl<T: FinancialStatement> StatementHistory<T> {
+    pub fn fill_history(&mut self, json_data: &Value) -> Result<(), FundamentalError> {
         let mut tmp = T::default();
-        self.records = tmp.parse_history(json_data)?;
+        tmp.set_parse_mode(self.parse_mode);
+        tmp.set_taxonomy(self.taxonomy);
+        tmp.set_currency(self.currency.clone());
+        tmp.set_as_of(self.as_of);
+        self.records = match self.mode {
+            HistoryMode::Annual => tmp.parse_history(json_data)?,
+            HistoryMode::Quarterly | HistoryMode::SemiAnnual => {
+                tmp.parse_interim_history(json_data, self.mode)?
+            }
+        };
+        self.warnings = std::mem::take(&mut tmp.get_metadata().warnings);
         Ok(())
     }
 }
 
+/// Statement field and the GAAP concepts it can be reported under, in order of preference
+#[derive(Debug, Clone, Copy)]
+pub struct ConceptMapping {
+    pub field: &'static str,
+    pub concepts: &'static [&'static str],
+    /// Whether a company not reporting any of the concepts is an error
+    pub required: bool,
+    pub unit: UnitKind,
+    /// Whether facts of one filing for the same period add up, one per class of stock
+    pub per_class: bool,
+}
+
+impl ConceptMapping {
+    pub const fn new(field: &'static str, concepts: &'static [&'static str]) -> Self {
+        Self {
+            field,
+            concepts,
+            required: true,
+            unit: UnitKind::Currency,
+            per_class: false,
+        }
+    }
+
+    /// Field that many companies do not report, left `None` when missing
+    pub const fn optional(field: &'static str, concepts: &'static [&'static str]) -> Self {
+        Self {
+            field,
+            concepts,
+            required: false,
+            unit: UnitKind::Currency,
+            per_class: false,
+        }
+    }
+
+    /// Same mapping for concepts reported per share or as a number of shares
+    pub const fn in_unit(self, unit: UnitKind) -> Self {
+        Self { unit, ..self }
+    }
+
+    /// Same mapping for concepts reported once per class of stock, summed per filing
+    pub const fn per_class(self) -> Self {
+        Self {
+            per_class: true,
+            ..self
+        }
+    }
+}
+
+/// Accounting identity `field == sum(sign * term)` checked after parsing
+#[derive(Debug, Clone, Copy)]
+pub struct ConsistencyCheck {
+    pub field: &'static str,
+    pub terms: &'static [(i64, &'static str)],
+}
+
+impl ConsistencyCheck {
+    pub const fn new(field: &'static str, terms: &'static [(i64, &'static str)]) -> Self {
+        Self { field, terms }
+    }
+}
+
+/// Facts of every candidate concept of a field, in order of preference
+type CandidateFacts = Vec<(&'static str, Vec<Fact>)>;
+
 pub trait FinancialStatement: Default {
     const MAX_HISTORY_YEARS: usize = 5;
-    /// Get GAAP tags of  financial statements
-    fn get_gaap_tags(&self) -> &[&'static str];
+    /// Stable name of the statement, key of stored statements
+    const NAME: &'static str;
+
+    /// Get field-to-concept mappings of financial statements in a taxonomy
+    fn get_concept_mappings(&self, taxonomy: Taxonomy) -> &[ConceptMapping];
 
     /// Get meta data
     fn get_metadata(&mut self) -> &mut MetaData;
 
-    /// Set GAAP tags to struct fields
-    fn set_gaap_value(&mut self, gaap_tag: &str, value: i64);
+    /// Meta data, read only
+    fn meta_data(&self) -> &MetaData;
+
+    /// Set value of an amount or per-share field
+    fn set_field_value(&mut self, field: &str, value: Money);
+
+    /// Get value of an amount or per-share field, `None` for an unknown or unreported field
+    fn get_field_value(&self, field: &str) -> Option<Money>;
+
+    /// Set value of a share count field
+    fn set_share_count(&mut self, _field: &str, _value: i64) {}
+
+    /// Get value of a share count field, `None` for an unknown or unreported field
+    fn get_share_count(&self, _field: &str) -> Option<i64> {
+        None
+    }
+
+    /// Taxonomy the statement always reads, whatever is set or detected
+    fn fixed_taxonomy(&self) -> Option<Taxonomy> {
+        None
+    }
+
+    /// Accounting identities between fields of the statement
+    fn get_consistency_checks(&self) -> &[ConsistencyCheck] {
+        &[]
+    }
+
+    fn set_parse_mode(&mut self, parse_mode: ParseMode) {
+        self.get_metadata().parse_mode = parse_mode;
+    }
+
+    /// Taxonomy to read, `None` detects it from the facts
+    fn set_taxonomy(&mut self, taxonomy: Option<Taxonomy>) {
+        self.get_metadata().taxonomy = taxonomy;
+    }
+
+    /// Currency unit to read, `None` detects the reporting currency from the facts
+    fn set_currency(&mut self, currency: Option<String>) {
+        self.get_metadata().currency = currency;
+    }
 
-    fn parse_quarly_latest(&mut self, json_data: &Value) -> Result<(), Box<dyn std::error::Error>> {
-        let facts = Self::extract_us_gaap(json_data)?;
-        let gaap_tags = self.get_gaap_tags().to_vec();
-        for gaap_tag in gaap_tags {
-            let facts_data = Self::extract_gaap_tag_in_unit_usd(facts, gaap_tag)?;
-            let latest_data = facts_data.last().unwrap();
-            self.fill_from_sec_json(latest_data, gaap_tag);
+    /// Only use facts filed on or before `as_of`, i.e. what was known on that date
+    fn set_as_of(&mut self, as_of: Option<NaiveDate>) {
+        self.get_metadata().as_of = as_of;
+    }
+
+    /// Empty history record reading the same taxonomy and currency as this statement
+    fn new_record(&mut self) -> Self {
+        let mut record = Self::default();
+        let meta_data = self.get_metadata();
+        let (taxonomy, currency) = (meta_data.taxonomy, meta_data.currency.clone());
+        let (parse_mode, as_of) = (meta_data.parse_mode, meta_data.as_of);
+        record.set_parse_mode(parse_mode);
+        record.set_taxonomy(taxonomy);
+        record.set_currency(currency);
+        record.set_as_of(as_of);
+        record
+    }
+
+    /// Year that "current" and history cutoffs are relative to
+    fn reference_year(&mut self) -> i32 {
+        self.get_metadata()
+            .as_of
+            .map_or_else(|| Utc::now().year(), |as_of| as_of.year())
+    }
+
+    /// Fill each field from the most recent period reported by any of its concepts.
+    /// When several concepts report that period, the first one in the mapping wins.
+    fn parse_quarly_latest(&mut self, json_data: &Value) -> Result<(), FundamentalError> {
+        let (taxonomy, facts) = self.resolve_facts(json_data)?;
+        let mappings = self.get_concept_mappings(taxonomy).to_vec();
+        self.resolve_currency(facts, &mappings);
+        for mapping in mappings {
+            let candidates = self.collect_candidate_facts(facts, &mapping)?;
+            let mut latest: Option<(&'static str, &Fact)> = None;
+            for (concept, concept_facts) in &candidates {
+                // Latest period, from the most recent filing reporting it
+                if let Some(fact) = concept_facts.iter().max_by(|a, b| a.end.cmp(&b.end))
+                    && latest.is_none_or(|(_, current)| fact.end > current.end)
+                {
+                    latest = Some((concept, fact));
+                }
+            }
+            if let Some((concept, fact)) = latest {
+                self.fill_from_sec_json(fact, &mapping, concept);
+            }
         }
+        self.validate();
         Ok(())
     }
 
-    fn parse_annually_latest(
-        &mut self,
-        json_data: &Value,
-    ) -> Result<(), Box<dyn std::error::Error>> {
-        let facts = Self::extract_us_gaap(json_data)?;
-        let current_year = Utc::now().year();
-        let gaap_tags = self.get_gaap_tags().to_vec();
-        for gaap_tag in gaap_tags {
-            let facts_data = Self::extract_gaap_tag_in_unit_usd(facts, gaap_tag)?;
-            for latest_data in facts_data.iter().rev() {
-                if latest_data["fy"].as_i64().unwrap() == current_year as i64 {
-                    self.fill_from_sec_json(latest_data, gaap_tag);
-                    break;
+    /// Fill each field from the latest fiscal year of an annual report (10-K, 20-F, 40-F).
+    /// Quarterly durations some annual reports include are ignored.
+    fn parse_annually_latest(&mut self, json_data: &Value) -> Result<(), FundamentalError> {
+        let (taxonomy, facts) = self.resolve_facts(json_data)?;
+        let mappings = self.get_concept_mappings(taxonomy).to_vec();
+        self.resolve_currency(facts, &mappings);
+        for mapping in mappings {
+            let candidates = self.collect_candidate_facts(facts, &mapping)?;
+            let mut found: Option<(&'static str, &Fact)> = None;
+            for (concept, concept_facts) in &candidates {
+                let latest = concept_facts
+                    .iter()
+                    .filter(|fact| fact.form_report().is_annual())
+                    .filter(|fact| fact.start.is_none() || fact.spans(&YEAR_DAYS))
+                    .max_by(|a, b| a.end.cmp(&b.end));
+                if let Some(fact) = latest
+                    && found.is_none_or(|(_, current)| fact.end > current.end)
+                {
+                    found = Some((concept, fact));
                 }
             }
+            if let Some((concept, fact)) = found {
+                self.fill_from_sec_json(fact, &mapping, concept);
+            }
         }
+        self.validate();
         Ok(())
     }
 
-    fn parse_history(
+    fn parse_history(&mut self, json_data: &Value) -> Result<Vec<Self>, FundamentalError> {
+        let (taxonomy, facts) = self.resolve_facts(json_data)?;
+        let mappings = self.get_concept_mappings(taxonomy).to_vec();
+        self.resolve_currency(facts, &mappings);
+        let cutoff_year = self.reference_year() - Self::MAX_HISTORY_YEARS as i32;
+        let mut history: BTreeMap<NaiveDate, Self> = BTreeMap::new();
+
+        for mapping in mappings {
+            let candidates = self.collect_candidate_facts(facts, &mapping)?;
+            for (concept, concept_facts) in &candidates {
+                for data in concept_facts.iter().rev() {
+                    // Quarterly durations some annual reports include are no fiscal year
+                    if !data.form_report().is_annual()
+                        || data.end.year() <= cutoff_year
+                        || data.start.is_some() && !data.spans(&YEAR_DAYS)
+                    {
+                        continue;
+                    }
+                    let entry = history.entry(data.end).or_insert_with(|| self.new_record());
+                    // An earlier concept in the mapping already reported this period
+                    if !entry.get_metadata().sources.contains_key(mapping.field) {
+                        entry.fill_from_sec_json(data, &mapping, concept);
+                        let meta_data = entry.get_metadata();
+                        if meta_data.accn == data.accn && meta_data.period == Some(data.period()) {
+                            meta_data.fiscal_year = data.annual_fiscal_year(concept_facts);
+                        }
+                        entry.record_revisions(&mapping, data, concept_facts);
+                    }
+                }
+            }
+        }
+        for entry in history.values_mut() {
+            entry.validate();
+        }
+        Ok(history.into_values().rev().collect())
+    }
+
+    /// One record per fiscal quarter, latest first
+    fn parse_quarterly_history(
         &mut self,
         json_data: &Value,
-    ) -> Result<Vec<Self>, Box<dyn std::error::Error>> {
-        let facts = Self::extract_us_gaap(json_data)?;
-        let gaap_tags = self.get_gaap_tags().to_vec();
-        let current_year = Utc::now().year();
-        let cutoff_year = current_year - Self::MAX_HISTORY_YEARS as i32;
-        let mut history: BTreeMap<&str, Self> = BTreeMap::new();
+    ) -> Result<Vec<Self>, FundamentalError> {
+        self.parse_interim_history(json_data, HistoryMode::Quarterly)
+    }
+
+    /// One record per fiscal half-year, latest first
+    fn parse_semiannual_history(
+        &mut self,
+        json_data: &Value,
+    ) -> Result<Vec<Self>, FundamentalError> {
+        self.parse_interim_history(json_data, HistoryMode::SemiAnnual)
+    }
+
+    /// One record per fiscal quarter or half-year, latest first.
+    /// Only the period a filing is about is kept, prior-year comparatives are skipped.
+    /// Periods are told apart by their length, not by calendar dates, so any fiscal
+    /// year end works. Durations of interim filings (10-Q, 6-K) of the right length
+    /// are used as reported. Periods reported only cumulatively (the last one in the
+    /// annual report, Q2/Q3 of year-to-date cash flows) are derived by subtracting the
+    /// cumulative value up to the previous period and marked as derived.
+    /// Instants of the annual report make up the last record of the year.
+    /// Per-share amounts and share counts are averages, they are never derived.
+    fn parse_interim_history(
+        &mut self,
+        json_data: &Value,
+        mode: HistoryMode,
+    ) -> Result<Vec<Self>, FundamentalError> {
+        let (period_days, reported_fp, annual_fp) = match mode {
+            HistoryMode::SemiAnnual => (HALF_YEAR_DAYS, Some("H1"), "H2"),
+            _ => (QUARTER_DAYS, None, "Q4"),
+        };
+        let (taxonomy, facts) = self.resolve_facts(json_data)?;
+        let mappings = self.get_concept_mappings(taxonomy).to_vec();
+        self.resolve_currency(facts, &mappings);
+        let cutoff_year = self.reference_year() - Self::MAX_HISTORY_YEARS as i32;
+        let mut candidates_by_field = Vec::with_capacity(mappings.len());
+        for mapping in mappings {
+            let candidates = self.collect_candidate_facts(facts, &mapping)?;
+            candidates_by_field.push((mapping, candidates));
+        }
 
-        for gaap_tag in gaap_tags {
-            let facts_data = Self::extract_gaap_tag_in_unit_usd(facts, gaap_tag)?;
-            for data in facts_data.iter().rev() {
-                if data["form"] != "10-K" {
+        // The period a filing is about ends last among the periods it reports
+        let mut filing_periods: HashMap<&str, NaiveDate> = HashMap::new();
+        for (_, candidates) in &candidates_by_field {
+            for fact in candidates
+                .iter()
+                .flat_map(|(_, concept_facts)| concept_facts)
+            {
+                let form_report = fact.form_report();
+                if !form_report.is_interim() && !form_report.is_annual() {
                     continue;
                 }
-                let end_date = data["end"].as_str().unwrap_or_default();
-                let year_prefix = end_date.split('-').next().unwrap_or("");
-                if let Ok(report_year) = year_prefix.parse::<i32>() {
-                    if report_year > cutoff_year {
-                        let entry = history.entry(end_date).or_insert_with(|| Self::default());
-                        entry.fill_from_sec_json(data, gaap_tag);
-                    } else {
-                        break;
+                let period_end = filing_periods.entry(&fact.accn).or_insert(fact.end);
+                *period_end = (*period_end).max(fact.end);
+            }
+        }
+        let is_filing_period = |fact: &Fact| {
+            (fact.form_report().is_interim() || fact.form_report().is_annual())
+                && filing_periods.get(fact.accn.as_str()) == Some(&fact.end)
+        };
+        let in_history = |fact: &Fact| fact.end.year() > cutoff_year;
+
+        let mut history: BTreeMap<NaiveDate, Self> = BTreeMap::new();
+        // Reported values first, so that they win over derived ones
+        for (mapping, candidates) in &candidates_by_field {
+            for (concept, concept_facts) in candidates {
+                for data in concept_facts.iter().rev() {
+                    if !is_filing_period(data) || !in_history(data) {
+                        continue;
+                    }
+                    let interim = data.form_report().is_interim();
+                    let fp = reported_fp.map(str::to_owned).or_else(|| data.fp.clone());
+                    let period = match &data.start {
+                        Some(_) if interim && data.spans(&period_days) => {
+                            Fact { fp, ..data.clone() }
+                        }
+                        // First and third quarter balance sheets are no half-year
+                        None if interim
+                            && (mode != HistoryMode::SemiAnnual
+                                || matches!(data.fp.as_deref(), Some("Q2" | "H1"))) =>
+                        {
+                            Fact { fp, ..data.clone() }
+                        }
+                        None if interim => continue,
+                        None => Fact {
+                            fp: Some(annual_fp.to_owned()),
+                            ..data.clone()
+                        },
+                        // Year-to-date and full-year durations
+                        _ => continue,
+                    };
+                    let entry = history.entry(data.end).or_insert_with(|| self.new_record());
+                    if !entry.get_metadata().sources.contains_key(mapping.field) {
+                        entry.fill_from_sec_json(&period, mapping, concept);
+                        entry.record_revisions(mapping, data, concept_facts);
                     }
-                } else {
-                    continue;
                 }
             }
         }
+
+        for (mapping, candidates) in &candidates_by_field {
+            if mapping.unit != UnitKind::Currency {
+                continue;
+            }
+            for (concept, concept_facts) in candidates {
+                for data in concept_facts.iter().rev() {
+                    if data.start.is_none()
+                        || data.spans(&period_days)
+                        || !is_filing_period(data)
+                        || !in_history(data)
+                    {
+                        continue;
+                    }
+                    // Cumulative value up to the previous period end, most recent filing first
+                    let derived = concept_facts
+                        .iter()
+                        .rev()
+                        .filter(|fact| fact.start == data.start && fact.end < data.end)
+                        .find_map(|earlier| {
+                            data.derive_last_period(earlier, &period_days, annual_fp)
+                        });
+                    let Some(period) = derived else {
+                        continue;
+                    };
+                    let entry = history.entry(data.end).or_insert_with(|| self.new_record());
+                    if !entry.get_metadata().sources.contains_key(mapping.field) {
+                        entry.fill_from_sec_json(&period, mapping, concept);
+                        if let Some(source) = entry.get_metadata().sources.get_mut(mapping.field) {
+                            source.derived = true;
+                        }
+                    }
+                }
+            }
+        }
+        for entry in history.values_mut() {
+            entry.validate();
+        }
         Ok(history.into_values().rev().collect())
     }
 
+    /// Check the accounting identities of the statement, recording every mismatch.
+    /// Identities whose fields are missing or come from different periods are skipped.
+    fn validate(&mut self) {
+        let mut issues = Vec::new();
+        let checks = self.get_consistency_checks().to_vec();
+        let sources = self.get_metadata().sources.clone();
+        for check in checks {
+            let fields = std::iter::once(check.field).chain(check.terms.iter().map(|(_, f)| *f));
+            let periods: Option<Vec<_>> = fields
+                .map(|field| sources.get(field).map(|source| source.period))
+                .collect();
+            let Some(periods) = periods else {
+                continue;
+            };
+            if periods.windows(2).any(|pair| pair[0] != pair[1]) {
+                continue;
+            }
+            let (Some(actual), Some(expected)) = (
+                self.get_field_value(check.field).map(|value| value.amount),
+                check
+                    .terms
+                    .iter()
+                    .try_fold(Decimal::ZERO, |sum, (sign, field)| {
+                        let value = self.get_field_value(field)?.amount;
+                        sum.checked_add(Decimal::from(*sign).checked_mul(value)?)
+                    }),
+            ) else {
+                continue;
+            };
+            if actual != expected {
+                warn!(
+                    "Inconsistent {}: reported {}, expected {}",
+                    check.field, actual, expected
+                );
+                issues.push(ValidationIssue {
+                    field: check.field.to_owned(),
+                    expected,
+                    actual,
+                });
+            }
+        }
+        self.get_metadata().validation_issues = issues;
+    }
+
+    /// Facts of the taxonomy set on the statement, or of the detected one
+    fn resolve_facts<'a>(
+        &mut self,
+        json_data: &'a Value,
+    ) -> Result<(Taxonomy, &'a Map<String, Value>), FundamentalError> {
+        let taxonomy = match self.fixed_taxonomy().or(self.get_metadata().taxonomy) {
+            Some(taxonomy) => taxonomy,
+            None => Taxonomy::detect(json_data).ok_or(FundamentalError::MissingFacts)?,
+        };
+        self.get_metadata().taxonomy = Some(taxonomy);
+        Ok((taxonomy, Self::extract_taxonomy(json_data, taxonomy)?))
+    }
+
     /// Extract field 'us-gaap' from SEC json raw response
-    fn extract_us_gaap(
+    fn extract_us_gaap(json_data: &Value) -> Result<&Map<String, Value>, FundamentalError> {
+        Self::extract_taxonomy(json_data, Taxonomy::UsGaap)
+    }
+
+    /// Extract a taxonomy field, e.g. 'ifrs-full', from SEC json raw response
+    fn extract_taxonomy(
         json_data: &Value,
-    ) -> Result<&Map<String, Value>, Box<dyn std::error::Error>> {
-        Ok(json_data["facts"]["us-gaap"]
+        taxonomy: Taxonomy,
+    ) -> Result<&Map<String, Value>, FundamentalError> {
+        json_data["facts"][taxonomy.key()]
             .as_object()
-            .ok_or_else(|| String::from("Parse error: could not find 'facts'"))?)
+            .ok_or(FundamentalError::MissingFacts)
+    }
+
+    /// Reporting currency set on the statement, or else the currency unit most facts
+    /// of the mapped concepts are reported in. USD wins ties and is the fallback.
+    fn resolve_currency(&mut self, facts: &Map<String, Value>, mappings: &[ConceptMapping]) {
+        if self.get_metadata().currency.is_some() {
+            return;
+        }
+        let mut counts: HashMap<&str, usize> = HashMap::new();
+        let concepts = mappings.iter().flat_map(|mapping| mapping.concepts);
+        for units in concepts.filter_map(|concept| facts.get(*concept)?["units"].as_object()) {
+            for (unit, unit_facts) in units {
+                if Self::is_currency(unit) {
+                    let count = unit_facts.as_array().map_or(0, Vec::len);
+                    *counts.entry(unit).or_default() += count;
+                }
+            }
+        }
+        let currency = counts
+            .into_iter()
+            .max_by_key(|(unit, count)| (*count, *unit == "USD"))
+            .map_or("USD", |(unit, _)| unit);
+        self.get_metadata().currency = Some(currency.to_owned());
+    }
+
+    /// ISO 4217 code, as opposed to units like "shares" or "USD/shares"
+    fn is_currency(unit: &str) -> bool {
+        unit.len() == 3 && unit.bytes().all(|byte| byte.is_ascii_uppercase())
     }
 
     /// Extract gaap tag in USD from 'us-gaap' field
     fn extract_gaap_tag_in_unit_usd<'a>(
         facts: &'a Map<String, Value>,
         gaap_tag: &str,
-    ) -> Result<&'a Vec<Value>, Box<dyn std::error::Error>> {
+    ) -> Result<&'a Vec<Value>, FundamentalError> {
+        Self::extract_gaap_tag_in_unit(facts, gaap_tag, "USD")
+    }
+
+    /// Extract gaap tag in a unit (e.g. "EUR", "shares") from a taxonomy field
+    fn extract_gaap_tag_in_unit<'a>(
+        facts: &'a Map<String, Value>,
+        gaap_tag: &str,
+        unit: &str,
+    ) -> Result<&'a Vec<Value>, FundamentalError> {
         let data = facts
             .get(gaap_tag)
-            .ok_or_else(|| String::from(&format!("Parse error: could not find {}", gaap_tag)))?;
-        Ok(data["units"]["USD"]
+            .ok_or_else(|| FundamentalError::MissingGaapTag {
+                tag: gaap_tag.to_owned(),
+            })?;
+        data["units"][unit]
             .as_array()
-            .ok_or_else(|| String::from("Parse error: could not find USD unit"))?)
+            .ok_or_else(|| FundamentalError::MissingUnit {
+                tag: gaap_tag.to_owned(),
+                unit: unit.to_owned(),
+            })
+    }
+
+    /// Convert every field from the reporting currency to USD, share counts excepted.
+    /// Instants use the period-end rate, durations the rate `basis` selects.
+    fn normalize_to_usd(
+        &mut self,
+        converter: &impl CurrencyConverter,
+        basis: RateBasis,
+    ) -> Result<(), FundamentalError> {
+        let meta_data = self.get_metadata();
+        let currency = meta_data
+            .currency
+            .clone()
+            .unwrap_or_else(|| String::from("USD"));
+        if currency == "USD" {
+            return Ok(());
+        }
+        let sources = meta_data.sources.clone();
+        let mut converted = Vec::new();
+        for (field, source) in sources
+            .into_iter()
+            .filter(|(_, s)| s.unit != UnitKind::Shares)
+        {
+            let end = source.period.end();
+            let missing_rate = || FundamentalError::MissingFxRate {
+                currency: currency.clone(),
+                date: end,
+            };
+            let rate = match (basis, source.period.start()) {
+                (RateBasis::PeriodAverage, Some(start)) => {
+                    converter.average_rate(&currency, start, end)
+                }
+                _ => converter.rate(&currency, end),
+            }
+            .and_then(|rate| Decimal::try_from(rate).ok())
+            .ok_or_else(missing_rate)?;
+            if let Some(value) = self.get_field_value(&field) {
+                converted.push((field, (value.amount * rate).round_dp(2)));
+            }
+        }
+        for (field, amount) in converted {
+            self.set_field_value(&field, Money::new(amount, "USD"));
+        }
+        let meta_data = self.get_metadata();
+        meta_data.reporting_currency = Some(currency);
+        meta_data.currency = Some(String::from("USD"));
+        Ok(())
+    }
+
+    /// Parsed facts of every concept of the mapping that the company reports,
+    /// ordered by filing date so that the most recent filing of a period comes last.
+    /// Facts filed after the as-of date are dropped.
+    /// A field none of whose concepts are reported fails in strict mode
+    /// and is recorded as a warning in tolerant mode.
+    fn collect_candidate_facts(
+        &mut self,
+        facts: &Map<String, Value>,
+        mapping: &ConceptMapping,
+    ) -> Result<CandidateFacts, FundamentalError> {
+        let as_of = self.get_metadata().as_of;
+        let currency = self.get_metadata().currency.clone();
+        let unit = mapping.unit.unit(currency.as_deref().unwrap_or("USD"));
+        let mut candidates = Vec::new();
+        let mut reported = false;
+        for concept in mapping.concepts {
+            let Ok(facts_data) = Self::extract_gaap_tag_in_unit(facts, concept, &unit) else {
+                continue;
+            };
+            let mut parsed_facts = self.parse_facts(concept, facts_data)?;
+            if mapping.per_class {
+                parsed_facts = Fact::sum_per_filing(parsed_facts);
+            }
+            reported |= !parsed_facts.is_empty();
+            if let Some(as_of) = as_of {
+                parsed_facts.retain(|fact| fact.filed.is_some_and(|filed| filed <= as_of));
+            }
+            parsed_facts.sort_by_key(|fact| fact.filed);
+            if !parsed_facts.is_empty() {
+                candidates.push((*concept, parsed_facts));
+            }
+        }
+        // Not yet filed as of the cutoff is not an error
+        if !reported && mapping.required {
+            self.handle_missing_facts(mapping)?;
+        }
+        Ok(candidates)
+    }
+
+    /// Parse raw facts of a GAAP tag.
+    /// Malformed facts fail in strict mode and are skipped with a warning in tolerant mode.
+    fn parse_facts(
+        &mut self,
+        gaap_tag: &str,
+        facts_data: &[Value],
+    ) -> Result<Vec<Fact>, FundamentalError> {
+        let mut parsed_facts = Vec::with_capacity(facts_data.len());
+        for value in facts_data {
+            match Fact::from_value(value) {
+                Ok(fact) => parsed_facts.push(fact),
+                Err(reason) => {
+                    let accn = value["accn"].as_str().map(str::to_owned);
+                    self.record_warning(gaap_tag, accn, reason)?
+                }
+            }
+        }
+        Ok(parsed_facts)
+    }
+
+    /// None of the concepts of a field has usable facts
+    fn handle_missing_facts(&mut self, mapping: &ConceptMapping) -> Result<(), FundamentalError> {
+        let gaap_tag = mapping.concepts.first().copied().unwrap_or(mapping.field);
+        match self.get_metadata().parse_mode {
+            ParseMode::Strict => Err(FundamentalError::MissingGaapTag {
+                tag: gaap_tag.to_owned(),
+            }),
+            ParseMode::Tolerant => {
+                self.record_warning(gaap_tag, None, String::from("no usable facts"))
+            }
+        }
+    }
+
+    fn record_warning(
+        &mut self,
+        gaap_tag: &str,
+        accn: Option<String>,
+        reason: String,
+    ) -> Result<(), FundamentalError> {
+        let meta_data = self.get_metadata();
+        if meta_data.parse_mode == ParseMode::Strict {
+            return Err(FundamentalError::MalformedFact {
+                tag: gaap_tag.to_owned(),
+                reason,
+            });
+        }
+        warn!("Skipping fact of {}: {}", gaap_tag, reason);
+        meta_data.warnings.push(ParseWarning {
+            gaap_tag: gaap_tag.to_owned(),
+            accn,
+            reason,
+        });
+        Ok(())
+    }
+
+    /// Record every filing that reported the period of `period_fact`, oldest first
+    fn record_revisions(
+        &mut self,
+        mapping: &ConceptMapping,
+        period_fact: &Fact,
+        concept_facts: &[Fact],
+    ) {
+        let mut revisions: Vec<Revision> = Vec::new();
+        for fact in concept_facts
+            .iter()
+            .filter(|fact| fact.same_period(period_fact))
+        {
+            if revisions.last().is_some_and(|last| last.accn == fact.accn) {
+                continue;
+            }
+            revisions.push(Revision {
+                accn: fact.accn.clone(),
+                filed: fact.filed,
+                form_report: fact.form_report(),
+                value: fact.val,
+            });
+        }
+        self.get_metadata()
+            .revisions
+            .insert(mapping.field.to_owned(), revisions);
     }
 
     /// Fill SEC response to internal FinancialStatement
-    fn fill_from_sec_json(&mut self, sec_data: &Value, gaap_tag: &str) {
+    fn fill_from_sec_json(
+        &mut self,
+        sec_data: &Fact,
+        mapping: &ConceptMapping,
+        concept: &'static str,
+    ) {
         let meta_data = self.get_metadata();
-        meta_data.start_date = sec_data["start"].as_str().unwrap_or("None").to_owned();
-        meta_data.end_date = sec_data["end"].as_str().unwrap_or("None").to_owned();
-        meta_data.form_report = FormReport::from(sec_data["form"].as_str().unwrap());
-        meta_data.fiscal_period = FiscalPeriod::from(sec_data["fp"].as_str().unwrap());
-        self.set_gaap_value(gaap_tag, sec_data["val"].as_i64().unwrap_or(0));
+        let period = sec_data.period();
+        // The record is about its first duration, or its first instant when it has none
+        if meta_data
+            .period
+            .is_none_or(|current| current.is_instant() && !period.is_instant())
+        {
+            meta_data.accn = sec_data.accn.clone();
+            meta_data.filed = sec_data.filed;
+            meta_data.period = Some(period);
+            meta_data.form_report = FormReport::from(sec_data.form.as_str());
+            meta_data.fiscal_period =
+                FiscalPeriod::from(sec_data.fp.as_deref().unwrap_or_default());
+            meta_data.fiscal_year = sec_data.fy;
+        }
+        meta_data.sources.insert(
+            mapping.field.to_owned(),
+            FieldSource {
+                concept: concept.to_owned(),
+                unit: mapping.unit,
+                accn: sec_data.accn.clone(),
+                filed: sec_data.filed,
+                period: sec_data.period(),
+                derived: false,
+            },
+        );
+        let currency = meta_data
+            .currency
+            .clone()
+            .unwrap_or_else(|| String::from("USD"));
+        match mapping.unit {
+            UnitKind::Currency | UnitKind::PerShare => {
+                self.set_field_value(mapping.field, Money::new(sec_data.val, currency))
+            }
+            UnitKind::Shares => {
+                if let Some(count) = sec_data.val.round().to_i64() {
+                    self.set_share_count(mapping.field, count);
+                }
+            }
+        }
     }
 }
 
@@ -137,23 +798,68 @@ pub trait FinancialStatement: Default {
 #[cfg(test)]
 mod unittests {
     use super::*;
+    use crate::common::Period;
     use serde_json::json;
 
+    const MOCK_CONCEPTS: [ConceptMapping; 2] = [
+        ConceptMapping::new("revenue", &["Revenues", "SalesRevenueNet"]),
+        ConceptMapping::new("net_income", &["NetIncomeLoss"]),
+    ];
+
+    const MOCK_IFRS_CONCEPTS: [ConceptMapping; 2] = [
+        ConceptMapping::new("revenue", &["Revenue"]),
+        ConceptMapping::new("net_income", &["ProfitLoss"]),
+    ];
+
     #[derive(Debug, Default)]
     struct MockIncomeStatement {
+        revenue: Option<Money>,
+        net_income: Option<Money>,
         metadata: MetaData,
     }
 
     impl FinancialStatement for MockIncomeStatement {
-        fn get_gaap_tags(&self) -> &[&'static str] {
-            &["Revenues", "NetIncomeLoss"]
+        const NAME: &'static str = "mock_income_statement";
+
+        fn get_concept_mappings(&self, taxonomy: Taxonomy) -> &[ConceptMapping] {
+            match taxonomy {
+                Taxonomy::UsGaap => &MOCK_CONCEPTS,
+                Taxonomy::IfrsFull => &MOCK_IFRS_CONCEPTS,
+                Taxonomy::Dei => &[],
+            }
         }
 
         fn get_metadata(&mut self) -> &mut MetaData {
             &mut self.metadata
         }
 
-        fn set_gaap_value(&mut self, _gaap_tag: &str, _value: i64) {}
+        fn meta_data(&self) -> &MetaData {
+            &self.metadata
+        }
+
+        fn set_field_value(&mut self, field: &str, value: Money) {
+            match field {
+                "revenue" => self.revenue = Some(value),
+                "net_income" => self.net_income = Some(value),
+                _ => {}
+            }
+        }
+
+        fn get_field_value(&self, field: &str) -> Option<Money> {
+            match field {
+                "revenue" => self.revenue.clone(),
+                "net_income" => self.net_income.clone(),
+                _ => None,
+            }
+        }
+    }
+
+    fn usd(amount: i64) -> Option<Money> {
+        Some(Money::new(amount, "USD"))
+    }
+
+    fn date(value: &str) -> NaiveDate {
+        Fact::parse_date(value).unwrap()
     }
 
     fn create_mock_sec_json(current_year: i32) -> Value {
@@ -188,8 +894,8 @@ mod unittests {
         let json_data = create_mock_sec_json(current_year);
         let result = MockIncomeStatement::extract_us_gaap(&json_data);
         assert!(result.is_ok());
-        for tag in mock_obj.get_gaap_tags() {
-            assert!(result.as_ref().unwrap().contains_key(*tag));
+        for mapping in mock_obj.get_concept_mappings(Taxonomy::UsGaap) {
+            assert!(result.as_ref().unwrap().contains_key(mapping.concepts[0]));
         }
     }
 
@@ -212,10 +918,10 @@ mod unittests {
         let facts = MockIncomeStatement::extract_us_gaap(&json_data);
         assert!(facts.is_ok());
 
-        for gaap_tag in mock_obj.get_gaap_tags() {
+        for mapping in mock_obj.get_concept_mappings(Taxonomy::UsGaap) {
             let facts_data = MockIncomeStatement::extract_gaap_tag_in_unit_usd(
                 facts.as_ref().unwrap(),
-                gaap_tag,
+                mapping.concepts[0],
             );
             assert!(facts_data.is_ok());
             assert_eq!(facts_data.unwrap().len(), 2);
@@ -233,4 +939,517 @@ mod unittests {
             "Parse error: could not find MissingTag"
         );
     }
+
+    fn create_malformed_sec_json() -> Value {
+        json!({
+            "facts": {
+                "us-gaap": {
+                    "Revenues": {
+                        "units": {
+                            "USD": [
+                                {"val": 2000, "form": "10-Q", "fp": null, "fy": null, "end": "2024-12-31"},
+                                {"val": "n/a", "form": "10-Q", "fp": "Q1", "fy": 2025, "end": "2025-03-31", "accn": "0001"},
+                            ]
+                        }
+                    },
+                    "NetIncomeLoss": {
+                        "units": {
+                            "USD": []
+                        }
+                    }
+                }
+            }
+        })
+    }
+
+    #[test]
+    fn test_parse_strict_malformed_fact() {
+        let mut mock_obj = MockIncomeStatement::default();
+        let result = mock_obj.parse_quarly_latest(&create_malformed_sec_json());
+        assert!(matches!(
+            result,
+            Err(FundamentalError::MalformedFact { tag, .. }) if tag == "Revenues"
+        ));
+    }
+
+    #[test]
+    fn test_parse_tolerant_records_warnings() {
+        let mut mock_obj = MockIncomeStatement::default();
+        mock_obj.set_parse_mode(ParseMode::Tolerant);
+        mock_obj
+            .parse_quarly_latest(&create_malformed_sec_json())
+            .unwrap();
+        let warnings = &mock_obj.get_metadata().warnings;
+        assert_eq!(warnings.len(), 2);
+        assert_eq!(warnings[0].gaap_tag, "Revenues");
+        assert_eq!(warnings[0].accn.as_deref(), Some("0001"));
+        assert_eq!(warnings[1].gaap_tag, "NetIncomeLoss");
+        let period = mock_obj.get_metadata().period;
+        assert_eq!(period, Some(Period::Instant(date("2024-12-31"))));
+    }
+
+    #[test]
+    fn test_parse_annually_latest_null_fy() {
+        let mut mock_obj = MockIncomeStatement::default();
+        mock_obj.set_parse_mode(ParseMode::Tolerant);
+        assert!(
+            mock_obj
+                .parse_annually_latest(&create_malformed_sec_json())
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn test_fill_history_tolerant() {
+        let mut history = StatementHistory::<MockIncomeStatement> {
+            parse_mode: ParseMode::Tolerant,
+            ..Default::default()
+        };
+        history.fill_history(&create_malformed_sec_json()).unwrap();
+        assert!(history.records.is_empty());
+        assert_eq!(history.warnings.len(), 2);
+        assert_eq!(history.warnings[0].gaap_tag, "Revenues");
+        assert_eq!(history.warnings[1].gaap_tag, "NetIncomeLoss");
+    }
+
+    fn create_fallback_sec_json() -> Value {
+        let current_year = Utc::now().year();
+        let fy = |offset: i32| format!("{}-12-31", current_year - offset);
+        json!({
+            "facts": {
+                "us-gaap": {
+                    "Revenues": {
+                        "units": {
+                            "USD": [
+                                {"val": 200, "form": "10-K", "fp": "FY", "fy": current_year - 1, "end": fy(1), "accn": "a2"},
+                            ]
+                        }
+                    },
+                    "SalesRevenueNet": {
+                        "units": {
+                            "USD": [
+                                {"val": 90, "form": "10-K", "fp": "FY", "fy": current_year - 2, "end": fy(2), "accn": "a1"},
+                                {"val": 199, "form": "10-K", "fp": "FY", "fy": current_year - 1, "end": fy(1), "accn": "a2"},
+                                {"val": 300, "form": "10-Q", "fp": "Q1", "fy": current_year, "end": format!("{}-03-31", current_year), "accn": "a3"},
+                            ]
+                        }
+                    },
+                    "NetIncomeLoss": {
+                        "units": {
+                            "USD": [
+                                {"val": 20, "form": "10-K", "fp": "FY", "fy": current_year - 1, "end": fy(1), "accn": "a2"},
+                            ]
+                        }
+                    }
+                }
+            }
+        })
+    }
+
+    #[test]
+    fn test_parse_history_concept_fallback() {
+        let mut mock_obj = MockIncomeStatement::default();
+        let mut history = mock_obj.parse_history(&create_fallback_sec_json()).unwrap();
+        assert_eq!(history.len(), 2);
+
+        // First concept of the mapping wins when both report the period
+        assert_eq!(history[0].revenue, usd(200));
+        assert_eq!(
+            history[0].get_metadata().sources["revenue"].concept,
+            "Revenues"
+        );
+        // Older period only reported under the fallback concept
+        assert_eq!(history[1].revenue, usd(90));
+        assert_eq!(
+            history[1].get_metadata().sources["revenue"].concept,
+            "SalesRevenueNet"
+        );
+        assert!(!history[1].get_metadata().sources.contains_key("net_income"));
+    }
+
+    #[test]
+    fn test_parse_quarly_latest_concept_fallback() {
+        let mut mock_obj = MockIncomeStatement::default();
+        mock_obj
+            .parse_quarly_latest(&create_fallback_sec_json())
+            .unwrap();
+        // Latest period is only reported under the fallback concept
+        assert_eq!(mock_obj.revenue, usd(300));
+        let source = &mock_obj.get_metadata().sources["revenue"];
+        assert_eq!(source.concept, "SalesRevenueNet");
+        assert_eq!(source.accn, "a3");
+        assert_eq!(mock_obj.net_income, usd(20));
+    }
+
+    #[test]
+    fn test_parse_missing_every_concept() {
+        let json_data = json!({"facts": {"us-gaap": {"Revenues": {"units": {"USD": [
+            {"val": 1, "form": "10-Q", "fp": "Q1", "fy": 2025, "end": "2025-03-31"}
+        ]}}}}});
+        let mut mock_obj = MockIncomeStatement::default();
+        assert!(matches!(
+            mock_obj.parse_quarly_latest(&json_data),
+            Err(FundamentalError::MissingGaapTag { tag }) if tag == "NetIncomeLoss"
+        ));
+
+        let mut mock_obj = MockIncomeStatement::default();
+        mock_obj.set_parse_mode(ParseMode::Tolerant);
+        mock_obj.parse_quarly_latest(&json_data).unwrap();
+        assert_eq!(mock_obj.revenue, usd(1));
+        assert_eq!(mock_obj.get_metadata().warnings.len(), 1);
+    }
+
+    #[test]
+    fn test_parse_quarterly_history() {
+        let year = Utc::now().year() - 1;
+        let fact = |val: i64, start: String, end: String, accn: &str, fp: &str| json!({"val": val, "form": "10-Q", "fp": fp, "fy": year, "start": start, "end": end, "accn": accn});
+        let json_data = json!({"facts": {"us-gaap": {
+            "Revenues": {"units": {"USD": [
+                fact(10, format!("{}-01-01", year), format!("{}-03-31", year), "q1", "Q1"),
+                // Prior-year comparative and year-to-date duration of the Q2 filing
+                fact(8, format!("{}-04-01", year - 1), format!("{}-06-30", year - 1), "q2", "Q2"),
+                fact(30, format!("{}-01-01", year), format!("{}-06-30", year), "q2", "Q2"),
+                fact(20, format!("{}-04-01", year), format!("{}-06-30", year), "q2", "Q2"),
+            ]}},
+            "NetIncomeLoss": {"units": {"USD": [
+                {"val": 5, "form": "10-K", "fp": "FY", "fy": year - 1, "start": format!("{}-01-01", year - 1), "end": format!("{}-12-31", year - 1), "accn": "k"},
+                fact(2, format!("{}-04-01", year), format!("{}-06-30", year), "q2", "Q2"),
+            ]}},
+        }}});
+
+        let mut history = StatementHistory::<MockIncomeStatement> {
+            mode: HistoryMode::Quarterly,
+            ..Default::default()
+        };
+        history.fill_history(&json_data).unwrap();
+        let records = &mut history.records;
+        assert_eq!(records.len(), 2);
+
+        assert_eq!(records[0].revenue, usd(20));
+        assert_eq!(records[0].net_income, usd(2));
+        let meta_data = records[0].get_metadata();
+        assert_eq!(meta_data.fiscal_period, FiscalPeriod::Q2);
+        assert_eq!(
+            meta_data.period,
+            Some(Period::Duration {
+                start: date(&format!("{}-04-01", year)),
+                end: date(&format!("{}-06-30", year)),
+            })
+        );
+
+        assert_eq!(records[1].revenue, usd(10));
+        assert_eq!(records[1].get_metadata().fiscal_period, FiscalPeriod::Q1);
+    }
+
+    fn create_restated_sec_json() -> Value {
+        let fact = |val: i64, end: &str, accn: &str, filed: &str| json!({"val": val, "form": "10-K", "fp": "FY", "fy": 2024, "start": format!("{}-01-01", &end[..4]), "end": end, "accn": accn, "filed": filed});
+        json!({"facts": {"us-gaap": {
+            "Revenues": {"units": {"USD": [
+                // Restated in the next year's 10-K, listed before the original
+                fact(95, "2023-12-31", "restated", "2025-02-18"),
+                fact(100, "2023-12-31", "original", "2024-02-20"),
+                fact(120, "2024-12-31", "restated", "2025-02-18"),
+            ]}},
+            "NetIncomeLoss": {"units": {"USD": [
+                fact(10, "2023-12-31", "original", "2024-02-20"),
+                fact(12, "2024-12-31", "restated", "2025-02-18"),
+            ]}},
+        }}})
+    }
+
+    #[test]
+    fn test_history_skips_quarterly_durations_of_annual_reports() {
+        // The 10-K also reports the fourth quarter, filed after the full year
+        let fact = |val: i64, start: &str, end: &str, fp: &str| json!({"val": val, "form": "10-K", "fp": fp, "fy": 2024, "start": start, "end": end, "accn": "annual", "filed": "2025-02-18"});
+        let json_data = json!({"facts": {"us-gaap": {
+            "Revenues": {"units": {"USD": [
+                fact(400, "2024-01-01", "2024-12-31", "FY"),
+                fact(110, "2024-10-01", "2024-12-31", "Q4"),
+                fact(90, "2023-10-01", "2023-12-31", "Q4"),
+            ]}},
+            "NetIncomeLoss": {"units": {"USD": [
+                fact(40, "2024-01-01", "2024-12-31", "FY"),
+            ]}},
+        }}});
+        let mut history = StatementHistory::<MockIncomeStatement>::default();
+        history.fill_history(&json_data).unwrap();
+        assert_eq!(history.records.len(), 1);
+        let record = &mut history.records[0];
+        assert_eq!(record.revenue, usd(400));
+        assert_eq!(
+            record.get_metadata().sources["revenue"].period.days(),
+            Some(365)
+        );
+    }
+
+    #[test]
+    fn test_history_uses_most_recent_filing() {
+        let mut history = StatementHistory::<MockIncomeStatement>::default();
+        history.fill_history(&create_restated_sec_json()).unwrap();
+        assert_eq!(history.records.len(), 2);
+        assert_eq!(history.records[1].revenue, usd(95));
+        assert_eq!(
+            history.records[1].get_metadata().sources["revenue"].accn,
+            "restated"
+        );
+    }
+
+    #[test]
+    fn test_history_as_of() {
+        let mut history = StatementHistory::<MockIncomeStatement> {
+            as_of: NaiveDate::from_ymd_opt(2024, 6, 30),
+            ..Default::default()
+        };
+        history.fill_history(&create_restated_sec_json()).unwrap();
+        // Neither the restatement nor FY2024 were known yet
+        assert_eq!(history.records.len(), 1);
+        let record = &mut history.records[0];
+        assert_eq!(record.revenue, usd(100));
+        let meta_data = record.get_metadata();
+        assert_eq!(meta_data.accn, "original");
+        assert_eq!(meta_data.filed, Some(date("2024-02-20")));
+
+        // Before the first filing nothing is known, which is not an error
+        let mut mock_obj = MockIncomeStatement::default();
+        mock_obj.set_as_of(NaiveDate::from_ymd_opt(2024, 1, 1));
+        mock_obj
+            .parse_quarly_latest(&create_restated_sec_json())
+            .unwrap();
+        assert!(mock_obj.get_metadata().sources.is_empty());
+    }
+
+    #[test]
+    fn test_parse_quarly_latest_as_of() {
+        let mut mock_obj = MockIncomeStatement::default();
+        mock_obj.set_as_of(NaiveDate::from_ymd_opt(2024, 12, 31));
+        mock_obj
+            .parse_quarly_latest(&create_restated_sec_json())
+            .unwrap();
+        assert_eq!(mock_obj.revenue, usd(100));
+        assert_eq!(mock_obj.net_income, usd(10));
+    }
+
+    #[test]
+    fn test_history_tracks_amendments() {
+        let fact = |val: i64, form: &str, accn: &str, filed: &str| json!({"val": val, "form": form, "fp": "FY", "fy": 2024, "start": "2024-01-01", "end": "2024-12-31", "accn": accn, "filed": filed});
+        let json_data = json!({"facts": {"us-gaap": {
+            "Revenues": {"units": {"USD": [
+                fact(100, "10-K", "original", "2025-02-18"),
+                fact(90, "10-K/A", "amended", "2025-05-02"),
+            ]}},
+            "NetIncomeLoss": {"units": {"USD": [
+                fact(10, "10-K", "original", "2025-02-18"),
+            ]}},
+        }}});
+        let mut history = StatementHistory::<MockIncomeStatement> {
+            as_of: NaiveDate::from_ymd_opt(2025, 12, 31),
+            ..Default::default()
+        };
+        history.fill_history(&json_data).unwrap();
+        assert_eq!(history.records.len(), 1);
+        let record = &mut history.records[0];
+        assert_eq!(record.revenue, usd(90));
+
+        let meta_data = record.get_metadata();
+        assert_eq!(meta_data.sources["revenue"].accn, "amended");
+        let revisions = &meta_data.revisions["revenue"];
+        assert_eq!(revisions.len(), 2);
+        assert_eq!(revisions[0].accn, "original");
+        assert_eq!(revisions[1].form_report, FormReport::AnnuallyAmendment);
+        assert_eq!(revisions[1].filed, Some(date("2025-05-02")));
+        assert_eq!(meta_data.restatement("revenue"), Some(Decimal::from(-10)));
+        assert_eq!(meta_data.restatement("net_income"), None);
+    }
+
+    fn create_foreign_issuer_sec_json() -> Value {
+        // Fiscal year from April to March, half-year results on 6-K
+        let year = Utc::now().year() - 1;
+        let fact = |val: i64, start: String, end: String, form: &str, fp: &str, accn: &str| json!({"val": val, "form": form, "fp": fp, "fy": year, "start": start, "end": end, "accn": accn, "filed": format!("{}-06-30", year)});
+        json!({"facts": {"us-gaap": {
+            "Revenues": {"units": {"USD": [
+                fact(40, format!("{}-04-01", year - 1), format!("{}-09-30", year - 1), "6-K", "H1", "h1"),
+                fact(100, format!("{}-04-01", year - 1), format!("{}-03-31", year), "20-F", "FY", "fy"),
+            ]}},
+            "NetIncomeLoss": {"units": {"USD": [
+                fact(4, format!("{}-04-01", year - 1), format!("{}-09-30", year - 1), "6-K", "H1", "h1"),
+                fact(10, format!("{}-04-01", year - 1), format!("{}-03-31", year), "20-F", "FY", "fy"),
+            ]}},
+        }}})
+    }
+
+    #[test]
+    fn test_foreign_issuer_history() {
+        let json_data = create_foreign_issuer_sec_json();
+        let mut annual = StatementHistory::<MockIncomeStatement>::default();
+        annual.fill_history(&json_data).unwrap();
+        assert_eq!(annual.records.len(), 1);
+        assert_eq!(annual.records[0].revenue, usd(100));
+        assert_eq!(
+            annual.records[0].get_metadata().form_report,
+            FormReport::Annually20F
+        );
+
+        let mut semiannual = StatementHistory::<MockIncomeStatement> {
+            mode: HistoryMode::SemiAnnual,
+            ..Default::default()
+        };
+        semiannual.fill_history(&json_data).unwrap();
+        let records = &mut semiannual.records;
+        assert_eq!(records.len(), 2);
+        assert_eq!(
+            (records[0].revenue.clone(), records[0].net_income.clone()),
+            (usd(60), usd(6))
+        );
+        let meta_data = records[0].get_metadata();
+        assert_eq!(meta_data.fiscal_period, FiscalPeriod::H2);
+        assert!(meta_data.sources["revenue"].derived);
+        let start = meta_data.period.and_then(|period| period.start()).unwrap();
+        assert_eq!((start.month(), start.day()), (10, 1));
+
+        assert_eq!(records[1].revenue, usd(40));
+        let meta_data = records[1].get_metadata();
+        assert_eq!(meta_data.fiscal_period, FiscalPeriod::H1);
+        assert_eq!(meta_data.form_report, FormReport::Interim6K);
+
+        let mut mock_obj = MockIncomeStatement::default();
+        mock_obj.parse_annually_latest(&json_data).unwrap();
+        assert_eq!(mock_obj.revenue, usd(100));
+        assert_eq!(mock_obj.get_metadata().fiscal_period, FiscalPeriod::FY);
+    }
+
+    #[test]
+    fn test_semiannual_history_tracks_6k_amendments() {
+        let year = Utc::now().year() - 1;
+        let fact = |val: i64, form: &str, accn: &str, filed: String| json!({"val": val, "form": form, "fp": "H1", "fy": year, "start": format!("{}-01-01", year), "end": format!("{}-06-30", year), "accn": accn, "filed": filed});
+        let json_data = json!({"facts": {"us-gaap": {
+            "Revenues": {"units": {"USD": [
+                fact(40, "6-K", "original", format!("{}-08-01", year)),
+                fact(42, "6-K/A", "amended", format!("{}-09-15", year)),
+            ]}},
+            "NetIncomeLoss": {"units": {"USD": [
+                fact(4, "6-K", "original", format!("{}-08-01", year)),
+            ]}},
+        }}});
+        let mut history = StatementHistory::<MockIncomeStatement> {
+            mode: HistoryMode::SemiAnnual,
+            ..Default::default()
+        };
+        history.fill_history(&json_data).unwrap();
+        let record = &mut history.records[0];
+        assert_eq!(record.revenue, usd(42));
+        let revisions = &record.get_metadata().revisions["revenue"];
+        assert_eq!(revisions.len(), 2);
+        assert_eq!(revisions[1].form_report, FormReport::Interim6KAmendment);
+        assert!(revisions[1].form_report.is_amendment());
+    }
+
+    #[test]
+    fn test_parse_ifrs_facts() {
+        let fact = |val: i64| json!({"units": {"USD": [{"val": val, "form": "20-F", "fp": "FY", "fy": 2024, "start": "2024-01-01", "end": "2024-12-31", "accn": "0001", "filed": "2025-03-01"}]}});
+        let json_data = json!({"facts": {
+            "us-gaap": {"Revenues": fact(1)},
+            "ifrs-full": {"Revenue": fact(100), "ProfitLoss": fact(10)},
+        }});
+        let mut mock_obj = MockIncomeStatement::default();
+        mock_obj.parse_annually_latest(&json_data).unwrap();
+        assert_eq!(
+            (mock_obj.revenue.clone(), mock_obj.net_income.clone()),
+            (usd(100), usd(10))
+        );
+        assert_eq!(mock_obj.get_metadata().taxonomy, Some(Taxonomy::IfrsFull));
+        assert_eq!(
+            mock_obj.get_metadata().sources["revenue"].concept,
+            "Revenue"
+        );
+
+        // An explicit taxonomy is not overridden
+        let mut mock_obj = MockIncomeStatement::default();
+        mock_obj.set_taxonomy(Some(Taxonomy::UsGaap));
+        assert!(matches!(
+            mock_obj.parse_annually_latest(&json_data),
+            Err(FundamentalError::MissingGaapTag { tag }) if tag == "NetIncomeLoss"
+        ));
+
+        let json_data = json!({"facts": {"dei": {}}});
+        assert!(matches!(
+            MockIncomeStatement::default().parse_quarly_latest(&json_data),
+            Err(FundamentalError::MissingFacts)
+        ));
+    }
+
+    fn create_eur_sec_json() -> Value {
+        let fact = |val: i64, start: Option<&str>| json!({"val": val, "form": "20-F", "fp": "FY", "fy": 2024, "start": start, "end": "2024-12-31", "accn": "0001"});
+        json!({"facts": {"ifrs-full": {
+            "Revenue": {"units": {"EUR": [fact(1000, Some("2024-01-01"))], "USD": [fact(5, Some("2024-01-01"))]}},
+            "ProfitLoss": {"units": {"EUR": [fact(100, Some("2024-01-01"))]}},
+        }}})
+    }
+
+    #[test]
+    fn test_parse_reporting_currency() {
+        let mut mock_obj = MockIncomeStatement::default();
+        mock_obj
+            .parse_annually_latest(&create_eur_sec_json())
+            .unwrap();
+        assert_eq!(mock_obj.revenue, Some(Money::new(1000, "EUR")));
+        assert_eq!(mock_obj.get_metadata().currency.as_deref(), Some("EUR"));
+
+        let mut mock_obj = MockIncomeStatement::default();
+        mock_obj.set_currency(Some(String::from("USD")));
+        mock_obj.set_parse_mode(ParseMode::Tolerant);
+        mock_obj
+            .parse_annually_latest(&create_eur_sec_json())
+            .unwrap();
+        assert_eq!(mock_obj.revenue, usd(5));
+        assert_eq!(mock_obj.get_metadata().warnings.len(), 1);
+    }
+
+    #[test]
+    fn test_history_records_keep_currency() {
+        let mut history = StatementHistory::<MockIncomeStatement>::default();
+        history.fill_history(&create_eur_sec_json()).unwrap();
+        assert_eq!(history.records.len(), 1);
+        let meta_data = history.records[0].get_metadata();
+        assert_eq!(meta_data.currency.as_deref(), Some("EUR"));
+        assert_eq!(meta_data.taxonomy, Some(Taxonomy::IfrsFull));
+    }
+
+    #[test]
+    fn test_normalize_to_usd() {
+        let rates = "date,currency,rate\n2024-06-30,EUR,1.2\n2024-12-31,EUR,1.1\n";
+        let table = crate::fx::FxRateTable::from_csv_reader(rates.as_bytes()).unwrap();
+
+        let mut mock_obj = MockIncomeStatement::default();
+        mock_obj
+            .parse_annually_latest(&create_eur_sec_json())
+            .unwrap();
+        mock_obj
+            .normalize_to_usd(&table, RateBasis::PeriodEnd)
+            .unwrap();
+        assert_eq!(
+            (mock_obj.revenue.clone(), mock_obj.net_income.clone()),
+            (usd(1100), usd(110))
+        );
+        let meta_data = mock_obj.get_metadata();
+        assert_eq!(meta_data.currency.as_deref(), Some("USD"));
+        assert_eq!(meta_data.reporting_currency.as_deref(), Some("EUR"));
+
+        let mut mock_obj = MockIncomeStatement::default();
+        mock_obj
+            .parse_annually_latest(&create_eur_sec_json())
+            .unwrap();
+        mock_obj
+            .normalize_to_usd(&table, RateBasis::PeriodAverage)
+            .unwrap();
+        assert_eq!(mock_obj.revenue, usd(1150));
+
+        let mut mock_obj = MockIncomeStatement::default();
+        mock_obj
+            .parse_annually_latest(&create_eur_sec_json())
+            .unwrap();
+        assert!(matches!(
+            mock_obj.normalize_to_usd(&crate::fx::FxRateTable::default(), RateBasis::PeriodEnd),
+            Err(FundamentalError::MissingFxRate { currency, .. }) if currency == "EUR"
+        ));
+    }
 }
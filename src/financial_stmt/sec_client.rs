@@ -1,18 +1,22 @@
 use std::collections::HashMap;
+use std::sync::Arc;
 
-use crate::interface::HttpClient;
+use crate::error::FundamentalError;
+use crate::financial_stmt::ticker_directory::TickerDirectory;
+use crate::interface::{HttpClient, cache::HttpCache, retry::RetryPolicy};
 
 use log::debug;
 use reqwest::header::{HeaderMap, HeaderValue, USER_AGENT};
 use serde_json::Value;
+use tokio::sync::OnceCell;
 
-#[derive(Debug, Default)]
+#[derive(Debug, Clone)]
 pub struct ConfiguredHttpClient {
     client: reqwest::Client,
 }
 
 impl ConfiguredHttpClient {
-    pub fn new() -> Result<Self, reqwest::Error> {
+    pub fn new() -> Result<Self, FundamentalError> {
         let user_agent_value = "(example@example.com)";
         let header_value = HeaderValue::from_str(user_agent_value)
             .expect("Error: User-Agent string literal should always be valid");
@@ -30,13 +34,57 @@ impl ConfiguredHttpClient {
     }
 }
 
+/// SEC endpoints used by `SecClient`, overridable to point at a local server
+#[derive(Debug, Clone)]
+pub struct SecEndpoints {
+    /// Company ticker endpoint, which is used for ticker-to-CIK mapping
+    pub ticker_lookup: String,
+    /// Simplified version of `ticker_lookup`
+    pub company_tickers: String,
+    /// Base URL for submission data
+    pub submissions_base: String,
+    /// Base URL for company facts data
+    pub company_facts_base: String,
+    /// EDGAR company browse feed, which carries the SIC code
+    pub sic_base: String,
+}
+
+impl Default for SecEndpoints {
+    fn default() -> Self {
+        Self {
+            ticker_lookup: String::from("https://www.sec.gov/files/company_tickers_exchange.json"),
+            company_tickers: String::from("https://www.sec.gov/files/company_tickers.json"),
+            submissions_base: String::from("https://data.sec.gov/submissions"),
+            company_facts_base: String::from("https://data.sec.gov/api/xbrl/companyfacts"),
+            sic_base: String::from(
+                "https://www.sec.gov/cgi-bin/browse-edgar?action=getcompany&output=atom",
+            ),
+        }
+    }
+}
+
+impl SecEndpoints {
+    /// Every endpoint under a single host, keeping SEC's path layout
+    pub fn with_base_url(base_url: &str) -> Self {
+        let base_url = base_url.trim_end_matches('/');
+        Self {
+            ticker_lookup: format!("{}/files/company_tickers_exchange.json", base_url),
+            company_tickers: format!("{}/files/company_tickers.json", base_url),
+            submissions_base: format!("{}/submissions", base_url),
+            company_facts_base: format!("{}/api/xbrl/companyfacts", base_url),
+            sic_base: format!(
+                "{}/cgi-bin/browse-edgar?action=getcompany&output=atom",
+                base_url
+            ),
+        }
+    }
+}
+
 #[derive(Debug, serde::Deserialize)]
 pub struct CompanyTickersExchange {
     pub cik: u32,
-    #[allow(dead_code)]
     pub name: Option<String>,
     pub ticker: Option<String>,
-    #[allow(dead_code)]
     pub exchange: Option<String>,
 }
 
@@ -62,28 +110,26 @@ struct SecResponse {
 }
 
 /// Client fetches data from SEC API
-#[derive(Debug, Default)]
+#[derive(Debug, Clone)]
 pub struct SecClient {
     ticker: String,
     http_client: ConfiguredHttpClient,
+    endpoints: SecEndpoints,
+    retry_policy: RetryPolicy,
+    cache: Option<HttpCache>,
+    /// Loaded on first CIK lookup, cloned clients share the same table
+    ticker_directory: OnceCell<Arc<TickerDirectory>>,
 }
 
 impl SecClient {
-    /// SEC API endpoint for company ticker, which is used for ticker-to-CIK mapping
-    const TICKER_LOOKUP_URL: &str = "https://www.sec.gov/files/company_tickers_exchange.json";
-    /// Simplified version of TICKER_LOOKUP_URL
-    const COMPANY_TICKERS_SIMPLIFIED: &str = "https://www.sec.gov/files/company_tickers.json";
-    /// Base URL for submission data
-    const SUBMISSIONS_BASE_URL: &str = "https://data.sec.gov/submissions";
-    /// Base URL for company facts data
-    const COMPANY_FACTS_BASE_URL: &str = "https://data.sec.gov/api/xbrl/companyfacts";
-    const SIC_BASE_URL: &str =
-        "https://www.sec.gov/cgi-bin/browse-edgar?action=getcompany&output=atom";
-
     pub fn new(ticker: String, http_client: ConfiguredHttpClient) -> Self {
         Self {
             ticker,
             http_client,
+            endpoints: SecEndpoints::default(),
+            retry_policy: RetryPolicy::default(),
+            cache: None,
+            ticker_directory: OnceCell::new(),
         }
     }
 
@@ -91,40 +137,106 @@ impl SecClient {
         self.ticker = ticker;
     }
 
+    pub fn set_endpoints(&mut self, endpoints: SecEndpoints) {
+        self.endpoints = endpoints;
+    }
+
+    pub fn endpoints(&self) -> &SecEndpoints {
+        &self.endpoints
+    }
+
+    pub fn set_retry_policy(&mut self, retry_policy: RetryPolicy) {
+        self.retry_policy = retry_policy;
+    }
+
+    /// Cache responses on disk, see `HttpCache` for TTL and offline options
+    pub fn set_cache(&mut self, cache: HttpCache) {
+        self.cache = Some(cache);
+    }
+
+    pub fn set_ticker_directory(&mut self, ticker_directory: Arc<TickerDirectory>) {
+        self.ticker_directory = OnceCell::from(ticker_directory);
+    }
+
+    /// Ticker-to-CIK table, downloaded once per client
+    pub async fn ticker_directory(&self) -> Result<Arc<TickerDirectory>, FundamentalError> {
+        let directory = self
+            .ticker_directory
+            .get_or_try_init(|| async {
+                debug!("Loading ticker directory");
+                let sec_response: SecResponse =
+                    self.fetch_json(&self.endpoints.ticker_lookup).await?;
+                Ok::<_, FundamentalError>(Arc::new(TickerDirectory::new(sec_response.data)))
+            })
+            .await?;
+        Ok(Arc::clone(directory))
+    }
+
     /// Fetch company's metadata Standard Industry Code (SIC)
-    pub async fn fetch_sic(&self) -> Result<String, Box<dyn std::error::Error>> {
+    pub async fn fetch_sic(&self) -> Result<String, FundamentalError> {
         let cik = self.ticker_to_cik().await?;
         let url = format!(
             "{}&CIK={}",
-            Self::SIC_BASE_URL,
-            cik.unwrap_or_default()
-                .strip_prefix("CIK")
-                .unwrap_or_default()
+            self.endpoints.sic_base,
+            cik.strip_prefix("CIK").unwrap_or_default()
         );
         debug!("Fetching SIC");
-        let data = Self::fetch_xml(&url).await?;
+        let data = self.fetch_xml(&url).await?;
+        Ok(data)
+    }
+
+    /// Fetch company's submissions, i.e. metadata and the most recent filings (`filings.recent`)
+    pub async fn fetch_submissions(&self) -> Result<Value, FundamentalError> {
+        let cik = self.lookup_cik().await?;
+        self.fetch_submissions_by_cik(cik).await
+    }
+
+    /// Same as `fetch_submissions` for a known CIK, which works whatever the ticker
+    pub async fn fetch_submissions_by_cik(&self, cik: u32) -> Result<Value, FundamentalError> {
+        let url = format!(
+            "{}/{}.json",
+            self.endpoints.submissions_base,
+            Self::add_cik_padding(cik)
+        );
+        debug!("Fetching submissions of CIK {}", cik);
+        let data = self.fetch_json(&url).await?;
+        Ok(data)
+    }
+
+    /// Same as `fetch_data` for a known CIK, which works whatever the ticker
+    pub async fn fetch_data_by_cik(&self, cik: u32) -> Result<Value, FundamentalError> {
+        let url = format!(
+            "{}/{}.json",
+            self.endpoints.company_facts_base,
+            Self::add_cik_padding(cik)
+        );
+        let data = self.fetch_json(&url).await?;
         Ok(data)
     }
 
     pub async fn fetch_all_company_tickers(
         &self,
-    ) -> Result<HashMap<String, CompanyTickers>, Box<dyn std::error::Error>> {
+    ) -> Result<HashMap<String, CompanyTickers>, FundamentalError> {
         type CompanyMap = HashMap<String, CompanyTickers>;
         debug!("Fetching all company tickers");
-        let json_response: CompanyMap = Self::fetch_json(Self::COMPANY_TICKERS_SIMPLIFIED).await?;
+        let json_response: CompanyMap = self.fetch_json(&self.endpoints.company_tickers).await?;
         Ok(json_response)
     }
 
-    async fn ticker_to_cik(&self) -> Result<Option<String>, Box<dyn std::error::Error>> {
-        let sec_response: SecResponse = Self::fetch_json(Self::TICKER_LOOKUP_URL).await?;
-        let company_tickers: Vec<CompanyTickersExchange> = sec_response.data;
-        for company_ticker in company_tickers {
-            if company_ticker.ticker.unwrap_or(String::from("")) == self.ticker {
-                return Ok(Some(Self::add_cik_padding(company_ticker.cik)));
-            }
-        }
-        Ok(None)
+    async fn ticker_to_cik(&self) -> Result<String, FundamentalError> {
+        Ok(Self::add_cik_padding(self.lookup_cik().await?))
+    }
+
+    async fn lookup_cik(&self) -> Result<u32, FundamentalError> {
+        let directory = self.ticker_directory().await?;
+        directory
+            .find_by_ticker(&self.ticker)
+            .map(|company| company.cik)
+            .ok_or_else(|| FundamentalError::TickerNotFound {
+                ticker: self.ticker.clone(),
+            })
     }
+
     fn add_cik_padding(cik: u32) -> String {
         let mut nr_digits = cik.to_string().len();
         let mut padding_digits: String = String::from("");
@@ -139,16 +251,20 @@ impl SecClient {
 }
 
 impl HttpClient<serde_json::Value> for SecClient {
-    type Error = reqwest::Error;
+    fn retry_policy(&self) -> RetryPolicy {
+        self.retry_policy.clone()
+    }
 
-    async fn fetch_data(&self) -> Result<Value, Self::Error> {
-        let cik = self.ticker_to_cik().await.unwrap_or_default();
-        let url = format!(
-            "{}/{}.json",
-            Self::COMPANY_FACTS_BASE_URL,
-            cik.unwrap_or_default()
-        );
-        let data = Self::fetch_json(&url).await?;
-        Ok(data)
+    fn cache(&self) -> Option<&HttpCache> {
+        self.cache.as_ref()
+    }
+
+    fn create_client(&self) -> Result<reqwest::Client, FundamentalError> {
+        Ok(self.http_client.client().clone())
+    }
+
+    async fn fetch_data(&self) -> Result<Value, FundamentalError> {
+        let cik = self.lookup_cik().await?;
+        self.fetch_data_by_cik(cik).await
     }
 }
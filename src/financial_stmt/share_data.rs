@@ -0,0 +1,128 @@
+use crate::common::{MetaData, Money, Taxonomy, UnitKind};
+use crate::financial_stmt::{ConceptMapping, FinancialStatement};
+
+use serde::{Deserialize, Serialize};
+
+/// Field-to-concept mapping of the cover page, the same for every filer
+const DEI_CONCEPTS: [ConceptMapping; 2] = [
+    ConceptMapping::new(
+        "shares_outstanding",
+        &["EntityCommonStockSharesOutstanding"],
+    )
+    .in_unit(UnitKind::Shares)
+    // Multi-class issuers report one fact per class with the same end and accn
+    .per_class(),
+    // Only reported on annual reports, as of the end of the second fiscal quarter
+    ConceptMapping::optional("public_float", &["EntityPublicFloat"]),
+];
+
+/// Share data from the cover page of filings (`dei` namespace)
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ShareData {
+    /// Common shares outstanding on the latest practicable date before filing,
+    /// all classes of stock together
+    pub shares_outstanding: Option<i64>,
+    /// Market value of the shares held by non-affiliates
+    pub public_float: Option<Money>,
+    #[serde(rename = "metadata")]
+    pub financial_facts: MetaData,
+}
+
+impl FinancialStatement for ShareData {
+    const NAME: &'static str = "share_data";
+
+    fn set_field_value(&mut self, field: &str, value: Money) {
+        if field == "public_float" {
+            self.public_float = Some(value);
+        }
+    }
+
+    fn get_field_value(&self, field: &str) -> Option<Money> {
+        match field {
+            "public_float" => self.public_float.clone(),
+            _ => None,
+        }
+    }
+
+    fn set_share_count(&mut self, field: &str, value: i64) {
+        if field == "shares_outstanding" {
+            self.shares_outstanding = Some(value);
+        }
+    }
+
+    fn get_share_count(&self, field: &str) -> Option<i64> {
+        match field {
+            "shares_outstanding" => self.shares_outstanding,
+            _ => None,
+        }
+    }
+
+    fn get_concept_mappings(&self, _taxonomy: Taxonomy) -> &[ConceptMapping] {
+        &DEI_CONCEPTS
+    }
+
+    fn fixed_taxonomy(&self) -> Option<Taxonomy> {
+        Some(Taxonomy::Dei)
+    }
+
+    fn get_metadata(&mut self) -> &mut MetaData {
+        &mut self.financial_facts
+    }
+
+    fn meta_data(&self) -> &MetaData {
+        &self.financial_facts
+    }
+}
+
+// --- Test ---
+#[cfg(test)]
+mod unittests {
+    use super::*;
+    use crate::common::Period;
+    use crate::ratios::Ratios;
+    use chrono::NaiveDate;
+    use serde_json::{Value, json};
+
+    const FIXTURE: &str = include_str!("../../tests/fixtures/companyfacts_CIK0001045810.json");
+
+    #[test]
+    fn test_parse_cover_page() {
+        let json_data: Value = serde_json::from_str(FIXTURE).unwrap();
+        let mut share_data = ShareData::default();
+        share_data.parse_quarly_latest(&json_data).unwrap();
+        assert_eq!(share_data.shares_outstanding, Some(245_100_000));
+        assert_eq!(
+            share_data.public_float,
+            Some(Money::new(2_900_000_000_000i64, "USD"))
+        );
+        let meta_data = share_data.get_metadata();
+        assert_eq!(meta_data.taxonomy, Some(Taxonomy::Dei));
+        assert_eq!(
+            meta_data.sources["shares_outstanding"].period,
+            Period::Instant(NaiveDate::from_ymd_opt(2025, 2, 10).unwrap())
+        );
+        let shares_outstanding = share_data.shares_outstanding.unwrap_or_default();
+        assert_eq!(
+            Ratios::market_cap(120.0, shares_outstanding as f64),
+            29_412_000_000.0
+        );
+    }
+
+    #[test]
+    fn test_shares_outstanding_sums_classes() {
+        let fact = |end: &str, val: i64, accn: &str, filed: &str| json!({"end": end, "val": val, "accn": accn, "fy": 2024, "fp": "FY", "form": "10-K", "filed": filed});
+        let json_data = json!({"facts": {"dei": {"EntityCommonStockSharesOutstanding": {"units": {"shares": [
+            fact("2024-01-26", 5_900_000_000, "0000000001-24-000010", "2024-02-02"),
+            fact("2024-01-26", 860_000_000, "0000000001-24-000010", "2024-02-02"),
+            fact("2025-01-24", 5_800_000_000, "0000000001-25-000010", "2025-02-04"),
+            fact("2025-01-24", 850_000_000, "0000000001-25-000010", "2025-02-04"),
+        ]}}}}});
+        let mut share_data = ShareData::default();
+        share_data.parse_quarly_latest(&json_data).unwrap();
+        assert_eq!(share_data.shares_outstanding, Some(6_650_000_000));
+        assert_eq!(
+            share_data.get_metadata().sources["shares_outstanding"].accn,
+            "0000000001-25-000010"
+        );
+    }
+}
@@ -0,0 +1,117 @@
+use crate::financial_stmt::sec_client::CompanyTickersExchange;
+
+use std::collections::HashMap;
+
+/// In-memory ticker-to-CIK table loaded once from SEC `company_tickers_exchange.json`.
+/// Lookups by ticker, CIK and company name are case-insensitive.
+#[derive(Debug, Default)]
+pub struct TickerDirectory {
+    companies: Vec<CompanyTickersExchange>,
+    by_ticker: HashMap<String, usize>,
+    /// A CIK can have several listings (e.g. share classes), first one wins
+    by_cik: HashMap<u32, usize>,
+    by_name: HashMap<String, usize>,
+}
+
+impl TickerDirectory {
+    pub fn new(companies: Vec<CompanyTickersExchange>) -> Self {
+        let mut by_ticker = HashMap::with_capacity(companies.len());
+        let mut by_cik = HashMap::with_capacity(companies.len());
+        let mut by_name = HashMap::with_capacity(companies.len());
+        for (idx, company) in companies.iter().enumerate() {
+            if let Some(ticker) = &company.ticker {
+                by_ticker.entry(Self::normalize(ticker)).or_insert(idx);
+            }
+            if let Some(name) = &company.name {
+                by_name.entry(Self::normalize(name)).or_insert(idx);
+            }
+            by_cik.entry(company.cik).or_insert(idx);
+        }
+        Self {
+            companies,
+            by_ticker,
+            by_cik,
+            by_name,
+        }
+    }
+
+    pub fn find_by_ticker(&self, ticker: &str) -> Option<&CompanyTickersExchange> {
+        self.by_ticker
+            .get(&Self::normalize(ticker))
+            .map(|idx| &self.companies[*idx])
+    }
+
+    pub fn find_by_cik(&self, cik: u32) -> Option<&CompanyTickersExchange> {
+        self.by_cik.get(&cik).map(|idx| &self.companies[*idx])
+    }
+
+    pub fn find_by_name(&self, name: &str) -> Option<&CompanyTickersExchange> {
+        self.by_name
+            .get(&Self::normalize(name))
+            .map(|idx| &self.companies[*idx])
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &CompanyTickersExchange> {
+        self.companies.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.companies.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.companies.is_empty()
+    }
+
+    fn normalize(key: &str) -> String {
+        key.trim().to_uppercase()
+    }
+}
+
+// --- Test ---
+#[cfg(test)]
+mod unittests {
+    use super::*;
+
+    fn company(cik: u32, name: &str, ticker: &str) -> CompanyTickersExchange {
+        CompanyTickersExchange {
+            cik,
+            name: Some(name.to_owned()),
+            ticker: Some(ticker.to_owned()),
+            exchange: Some("Nasdaq".to_owned()),
+        }
+    }
+
+    fn create_directory() -> TickerDirectory {
+        TickerDirectory::new(vec![
+            company(1045810, "NVIDIA CORP", "NVDA"),
+            company(1652044, "Alphabet Inc.", "GOOGL"),
+            company(1652044, "Alphabet Inc.", "GOOG"),
+        ])
+    }
+
+    #[test]
+    fn test_find_by_ticker_case_insensitive() {
+        let directory = create_directory();
+        assert_eq!(directory.find_by_ticker("nvda").unwrap().cik, 1045810);
+        assert_eq!(directory.find_by_ticker(" NVDA ").unwrap().cik, 1045810);
+        assert_eq!(directory.find_by_ticker("GOOG").unwrap().cik, 1652044);
+        assert!(directory.find_by_ticker("MISSING").is_none());
+    }
+
+    #[test]
+    fn test_find_by_cik_returns_first_listing() {
+        let directory = create_directory();
+        let company = directory.find_by_cik(1652044).unwrap();
+        assert_eq!(company.ticker.as_deref(), Some("GOOGL"));
+        assert!(directory.find_by_cik(1).is_none());
+    }
+
+    #[test]
+    fn test_find_by_name() {
+        let directory = create_directory();
+        assert_eq!(directory.find_by_name("nvidia corp").unwrap().cik, 1045810);
+        assert!(directory.find_by_name("nvidia").is_none());
+        assert_eq!(directory.len(), 3);
+    }
+}
@@ -0,0 +1,312 @@
+use crate::common::{FieldSource, Money, ParseWarning, Period, UnitKind};
+use crate::error::FundamentalError;
+use crate::financial_stmt::fact::QUARTER_DAYS;
+use crate::financial_stmt::{FinancialStatement, StatementHistory};
+
+use chrono::Days;
+use log::warn;
+
+/// Number of quarters making up the trailing twelve months
+const TTM_QUARTERS: usize = 4;
+
+impl<T: FinancialStatement> StatementHistory<T> {
+    /// Trailing twelve months statement from a quarterly history (`HistoryMode::Quarterly`).
+    /// Duration fields (`IncomeStatement`, `CashFlow`) are summed over the latest four
+    /// quarters, which must follow each other without gaps. Instant fields (`BalanceSheet`,
+    /// cash position) take the latest value. Weighted average share counts are averaged.
+    /// A duration field missing from one of the quarters is left out and recorded in
+    /// `warnings`.
+    pub fn trailing_twelve_months(&mut self) -> Result<T, FundamentalError> {
+        let Some(latest) = self.records.first_mut() else {
+            return Err(FundamentalError::InsufficientHistory {
+                needed: 1,
+                found: 0,
+            });
+        };
+        let latest_sources = latest.get_metadata().sources.clone();
+        let taxonomy = latest.get_metadata().taxonomy.unwrap_or_default();
+        let currency = latest.get_metadata().currency.clone();
+        let latest_period = latest.get_metadata().period;
+        let has_durations = latest_sources
+            .values()
+            .any(|source| !source.period.is_instant());
+        let periods = if has_durations {
+            self.contiguous_quarters()?
+        } else {
+            Vec::new()
+        };
+        let quarters = 0..periods.len().max(1);
+
+        let mut ttm = T::default();
+        ttm.set_taxonomy(Some(taxonomy));
+        ttm.set_currency(currency);
+        let mappings = ttm.get_concept_mappings(taxonomy).to_vec();
+        for mapping in mappings {
+            let Some(latest_source) = latest_sources.get(mapping.field) else {
+                continue;
+            };
+            if latest_source.period.is_instant() {
+                let latest = &self.records[0];
+                if let Some(value) = latest.get_field_value(mapping.field) {
+                    ttm.set_field_value(mapping.field, value);
+                }
+                if let Some(count) = latest.get_share_count(mapping.field) {
+                    ttm.set_share_count(mapping.field, count);
+                }
+                ttm.get_metadata()
+                    .sources
+                    .insert(mapping.field.to_owned(), latest_source.clone());
+                continue;
+            }
+
+            // Every quarter has to report the field
+            let mut sum: Option<Money> = None;
+            let mut share_sum = 0i64;
+            let mut first_start = None;
+            let mut complete = true;
+            let mut incomplete_accn = None;
+            for record in &mut self.records[quarters.clone()] {
+                incomplete_accn = Some(record.meta_data().accn.clone());
+                let source = record.get_metadata().sources.get(mapping.field).cloned();
+                let Some(start) = source.and_then(|source| source.period.start()) else {
+                    complete = false;
+                    break;
+                };
+                first_start = Some(start);
+                if mapping.unit == UnitKind::Shares {
+                    match record.get_share_count(mapping.field) {
+                        Some(count) => share_sum = share_sum.saturating_add(count),
+                        None => complete = false,
+                    }
+                    continue;
+                }
+                match (record.get_field_value(mapping.field), sum.as_mut()) {
+                    (Some(value), Some(total)) => match total.checked_add(&value) {
+                        Some(next) => *total = next,
+                        None => complete = false,
+                    },
+                    (Some(value), None) => sum = Some(value),
+                    (None, _) => complete = false,
+                }
+            }
+            if !complete {
+                warn!(
+                    "Leaving {} out of the trailing twelve months",
+                    mapping.field
+                );
+                self.warnings.push(ParseWarning {
+                    gaap_tag: latest_source.concept.clone(),
+                    accn: incomplete_accn,
+                    reason: format!(
+                        "{} is not reported for every quarter, left out of the trailing \
+                         twelve months",
+                        mapping.field
+                    ),
+                });
+                continue;
+            }
+            match (mapping.unit, sum) {
+                (UnitKind::Shares, _) => {
+                    ttm.set_share_count(mapping.field, share_sum / quarters.len() as i64)
+                }
+                (_, Some(sum)) => ttm.set_field_value(mapping.field, sum),
+                (_, None) => continue,
+            }
+            ttm.get_metadata().sources.insert(
+                mapping.field.to_owned(),
+                FieldSource {
+                    period: Period::new(first_start, latest_source.period.end()),
+                    derived: true,
+                    ..latest_source.clone()
+                },
+            );
+        }
+
+        let latest = self.records[0].get_metadata();
+        let fiscal_period = latest.fiscal_period;
+        let fiscal_year = latest.fiscal_year;
+        let meta_data = ttm.get_metadata();
+        meta_data.period = match (periods.last(), latest_period) {
+            (Some(earliest), Some(latest)) => Some(Period::new(earliest.start(), latest.end())),
+            _ => latest_period,
+        };
+        meta_data.fiscal_period = fiscal_period;
+        meta_data.fiscal_year = fiscal_year;
+        ttm.validate();
+        Ok(ttm)
+    }
+
+    /// Periods of the latest four quarters, checked to be consecutive
+    fn contiguous_quarters(&mut self) -> Result<Vec<Period>, FundamentalError> {
+        let mut periods = Vec::with_capacity(TTM_QUARTERS);
+        for record in self.records.iter_mut().take(TTM_QUARTERS) {
+            // Period of the duration fields, instants have no start
+            let period = record
+                .get_metadata()
+                .sources
+                .values()
+                .map(|source| source.period)
+                .find(|period| !period.is_instant());
+            let Some(period) = period else {
+                break;
+            };
+            if !period
+                .days()
+                .is_some_and(|days| QUARTER_DAYS.contains(&days))
+            {
+                break;
+            }
+            periods.push(period);
+        }
+        if periods.len() < TTM_QUARTERS {
+            return Err(FundamentalError::InsufficientHistory {
+                needed: TTM_QUARTERS,
+                found: periods.len(),
+            });
+        }
+
+        // Latest first: each quarter starts the day after the previous one ends
+        for pair in periods.windows(2) {
+            let (next, previous) = (pair[0], pair[1]);
+            let expected = previous.end().checked_add_days(Days::new(1));
+            if let Some(next_start) = next.start()
+                && expected != Some(next_start)
+            {
+                return Err(FundamentalError::NonContiguousQuarters {
+                    end: previous.end(),
+                    next_start,
+                });
+            }
+        }
+        Ok(periods)
+    }
+}
+
+// --- Test ---
+#[cfg(test)]
+mod unittests {
+    use crate::common::{HistoryMode, Money, Period};
+    use crate::error::FundamentalError;
+    use crate::financial_stmt::{
+        FinancialStatement, StatementHistory, balance_sheet::BalanceSheet, cash_flow::CashFlow,
+        income_statement::IncomeStatement,
+    };
+    use chrono::NaiveDate;
+    use serde_json::Value;
+
+    const FIXTURE: &str = include_str!("../../tests/fixtures/companyfacts_CIK0001045810.json");
+
+    fn usd(amount: i64) -> Option<Money> {
+        Some(Money::new(amount, "USD"))
+    }
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    fn quarterly_history<T: FinancialStatement>() -> StatementHistory<T> {
+        let json_data: Value = serde_json::from_str(FIXTURE).unwrap();
+        let mut history = StatementHistory::<T> {
+            mode: HistoryMode::Quarterly,
+            ..Default::default()
+        };
+        history.fill_history(&json_data).unwrap();
+        history
+    }
+
+    #[test]
+    fn test_ttm_income_statement() {
+        let mut history = quarterly_history::<IncomeStatement>();
+        let mut ttm = history.trailing_twelve_months().unwrap();
+        // Q1 to Q4 of 2024 add up to the full year
+        assert_eq!(ttm.total_revenue, usd(620_000_000));
+        assert_eq!(ttm.gross_profit, usd(372_000_000));
+        let meta_data = ttm.get_metadata();
+        assert_eq!(
+            meta_data.period,
+            Some(Period::Duration {
+                start: date(2024, 1, 1),
+                end: date(2024, 12, 31),
+            })
+        );
+        assert!(meta_data.validation_issues.is_empty());
+
+        // Q4 2023 to Q3 2024
+        history.records.remove(0);
+        let ttm = history.trailing_twelve_months().unwrap();
+        assert_eq!(
+            ttm.total_revenue,
+            usd(140_000_000 + 150_000_000 + 160_000_000 + 130_000_000)
+        );
+        // Weighted average shares are not derived for Q4 2023
+        assert_eq!(ttm.weighted_average_shares_basic, None);
+    }
+
+    #[test]
+    fn test_ttm_cash_flow() {
+        let mut history = quarterly_history::<CashFlow>();
+        let mut ttm = history.trailing_twelve_months().unwrap();
+        assert_eq!(ttm.operating_cash_flow, usd(204_800_000));
+        let start = ttm.get_metadata().period.and_then(|period| period.start());
+        assert_eq!(start, Some(date(2024, 1, 1)));
+        // Cash position is an instant
+        assert_eq!(
+            ttm.end_cash_flow_position,
+            history.records[0].end_cash_flow_position
+        );
+    }
+
+    #[test]
+    fn test_ttm_warns_about_incomplete_fields() {
+        let mut history = quarterly_history::<IncomeStatement>();
+        let q2 = &mut history.records[2];
+        q2.total_revenue = None;
+        q2.get_metadata().sources.remove("total_revenue");
+        let accn = q2.meta_data().accn.clone();
+
+        let ttm = history.trailing_twelve_months().unwrap();
+        assert_eq!(ttm.total_revenue, None);
+        assert_eq!(ttm.gross_profit, usd(372_000_000));
+        assert_eq!(history.warnings.len(), 1);
+        let warning = &history.warnings[0];
+        assert_eq!(warning.gaap_tag, "Revenues");
+        assert_eq!(warning.accn, Some(accn));
+        assert!(warning.reason.starts_with("total_revenue "));
+    }
+
+    #[test]
+    fn test_ttm_balance_sheet_takes_latest() {
+        let mut history = quarterly_history::<BalanceSheet>();
+        let ttm = history.trailing_twelve_months().unwrap();
+        assert_eq!(ttm.total_assets, usd(1_160_000_000));
+    }
+
+    #[test]
+    fn test_ttm_errors() {
+        let mut history = quarterly_history::<IncomeStatement>();
+        history.records.remove(1);
+        assert!(matches!(
+            history.trailing_twelve_months(),
+            Err(FundamentalError::NonContiguousQuarters { end, next_start })
+                if end == date(2024, 6, 30) && next_start == date(2024, 10, 1)
+        ));
+
+        history.records.truncate(3);
+        assert!(matches!(
+            history.trailing_twelve_months(),
+            Err(FundamentalError::InsufficientHistory {
+                needed: 4,
+                found: 3
+            })
+        ));
+
+        let mut annual = StatementHistory::<IncomeStatement>::default();
+        annual
+            .fill_history(&serde_json::from_str(FIXTURE).unwrap())
+            .unwrap();
+        assert!(matches!(
+            annual.trailing_twelve_months(),
+            Err(FundamentalError::InsufficientHistory { found: 0, .. })
+        ));
+    }
+}
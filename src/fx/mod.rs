@@ -0,0 +1,168 @@
+use crate::error::FundamentalError;
+
+use chrono::NaiveDate;
+use std::collections::{BTreeMap, HashMap};
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+/// Which exchange rate converts the value of a period
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum RateBasis {
+    /// Rate on the last day of the period
+    #[default]
+    PeriodEnd,
+    /// Mean of the daily rates over the period. Instants always use the period-end rate.
+    PeriodAverage,
+}
+
+/// Source of exchange rates to USD
+pub trait CurrencyConverter {
+    /// USD per unit of `currency` on `date`
+    fn rate(&self, currency: &str, date: NaiveDate) -> Option<f64>;
+
+    /// Mean USD per unit of `currency` between `start` and `end`, both included
+    fn average_rate(&self, currency: &str, start: NaiveDate, end: NaiveDate) -> Option<f64>;
+}
+
+/// Daily FX rates loaded from a local CSV file with a `date,currency,rate` header,
+/// `rate` being USD per unit of `currency`:
+///
+/// ```text
+/// date,currency,rate
+/// 2024-12-31,EUR,1.0389
+/// 2024-12-31,JPY,0.006361
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct FxRateTable {
+    /// Key: currency code, value: rates by date
+    rates: HashMap<String, BTreeMap<NaiveDate, f64>>,
+}
+
+#[derive(Debug, serde::Deserialize)]
+struct FxRateRecord {
+    date: NaiveDate,
+    currency: String,
+    rate: f64,
+}
+
+impl FxRateTable {
+    pub fn from_csv_path(path: impl AsRef<Path>) -> Result<Self, FundamentalError> {
+        Self::from_csv_reader(File::open(path)?)
+    }
+
+    pub fn from_csv_reader(reader: impl Read) -> Result<Self, FundamentalError> {
+        let mut table = Self::default();
+        let mut csv_reader = csv::Reader::from_reader(reader);
+        for record in csv_reader.deserialize() {
+            let record: FxRateRecord = record?;
+            table.insert(&record.currency, record.date, record.rate);
+        }
+        Ok(table)
+    }
+
+    pub fn insert(&mut self, currency: &str, date: NaiveDate, rate: f64) {
+        self.rates
+            .entry(currency_code(currency))
+            .or_default()
+            .insert(date, rate);
+    }
+}
+
+/// Currency codes are compared trimmed and uppercase
+fn currency_code(currency: &str) -> String {
+    currency.trim().to_ascii_uppercase()
+}
+
+impl CurrencyConverter for FxRateTable {
+    /// Latest rate on or before `date`, so that weekends and holidays use the last fixing
+    fn rate(&self, currency: &str, date: NaiveDate) -> Option<f64> {
+        let currency = currency_code(currency);
+        if currency == "USD" {
+            return Some(1.0);
+        }
+        let (_, rate) = self.rates.get(&currency)?.range(..=date).next_back()?;
+        Some(*rate)
+    }
+
+    fn average_rate(&self, currency: &str, start: NaiveDate, end: NaiveDate) -> Option<f64> {
+        let currency = currency_code(currency);
+        if currency == "USD" {
+            return Some(1.0);
+        }
+        let rates: Vec<f64> = self
+            .rates
+            .get(&currency)?
+            .range(start..=end)
+            .map(|(_, rate)| *rate)
+            .collect();
+        if rates.is_empty() {
+            return None;
+        }
+        Some(rates.iter().sum::<f64>() / rates.len() as f64)
+    }
+}
+
+// --- Test ---
+#[cfg(test)]
+mod unittests {
+    use super::*;
+
+    const RATES: &str = "date,currency,rate\n\
+        2024-09-30,EUR,1.10\n\
+        2024-10-31,EUR,1.08\n\
+        2024-11-29,EUR,1.06\n\
+        2024-12-30,eur,1.04\n";
+
+    fn date(value: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(value, "%Y-%m-%d").unwrap()
+    }
+
+    #[test]
+    fn test_rate() {
+        let table = FxRateTable::from_csv_reader(RATES.as_bytes()).unwrap();
+        assert_eq!(table.rate("EUR", date("2024-12-30")), Some(1.04));
+        // Falls back to the last fixing
+        assert_eq!(table.rate("EUR", date("2024-12-31")), Some(1.04));
+        assert_eq!(table.rate("EUR", date("2024-01-01")), None);
+        assert_eq!(table.rate("JPY", date("2024-12-31")), None);
+        assert_eq!(table.rate("USD", date("2024-12-31")), Some(1.0));
+    }
+
+    #[test]
+    fn test_currency_code_normalized() {
+        let table = FxRateTable::from_csv_reader(RATES.as_bytes()).unwrap();
+        assert_eq!(table.rate("eur", date("2024-12-30")), Some(1.04));
+        assert_eq!(table.rate(" EUR ", date("2024-12-30")), Some(1.04));
+        assert_eq!(table.rate("usd", date("2024-12-31")), Some(1.0));
+        assert!(
+            table
+                .average_rate("Eur", date("2024-10-01"), date("2024-12-31"))
+                .is_some()
+        );
+        assert_eq!(
+            table.average_rate("usd ", date("2025-01-01"), date("2025-03-31")),
+            Some(1.0)
+        );
+    }
+
+    #[test]
+    fn test_average_rate() {
+        let table = FxRateTable::from_csv_reader(RATES.as_bytes()).unwrap();
+        let average = table
+            .average_rate("EUR", date("2024-10-01"), date("2024-12-31"))
+            .unwrap();
+        assert!((average - 1.06).abs() < 1e-9);
+        assert_eq!(
+            table.average_rate("EUR", date("2025-01-01"), date("2025-03-31")),
+            None
+        );
+    }
+
+    #[test]
+    fn test_malformed_csv() {
+        let result =
+            FxRateTable::from_csv_reader("date,currency,rate\n2024-12-31,EUR,n/a\n".as_bytes());
+        assert!(matches!(result, Err(FundamentalError::Csv(_))));
+    }
+}
@@ -0,0 +1,185 @@
+use crate::error::FundamentalError;
+
+use log::debug;
+use reqwest::header::{ETAG, HeaderMap, LAST_MODIFIED};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Persistent HTTP response cache keyed by URL.
+/// Each entry is stored as `<key>.body` plus `<key>.meta.json` holding the validator headers.
+#[derive(Debug, Clone)]
+pub struct HttpCache {
+    pub dir: PathBuf,
+    /// Entries younger than this are served without contacting the server.
+    /// `None` revalidates every entry with a conditional GET.
+    pub ttl: Option<Duration>,
+    /// Serve only from the cache, never touch the network
+    pub offline_only: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CacheMetadata {
+    url: String,
+    etag: Option<String>,
+    last_modified: Option<String>,
+    /// Unix timestamp (seconds) of the last fetch or successful revalidation
+    fetched_at: u64,
+}
+
+#[derive(Debug, Clone)]
+pub struct CacheEntry {
+    pub body: Vec<u8>,
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+    pub fetched_at: SystemTime,
+}
+
+impl CacheEntry {
+    pub fn is_fresh(&self, ttl: Option<Duration>) -> bool {
+        let Some(ttl) = ttl else {
+            return false;
+        };
+        SystemTime::now()
+            .duration_since(self.fetched_at)
+            .map(|age| age < ttl)
+            .unwrap_or(true)
+    }
+}
+
+impl HttpCache {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            ttl: None,
+            offline_only: false,
+        }
+    }
+
+    pub fn load(&self, url: &str) -> Result<Option<CacheEntry>, FundamentalError> {
+        let (body_path, meta_path) = self.paths(url);
+        if !body_path.exists() || !meta_path.exists() {
+            return Ok(None);
+        }
+        let meta: CacheMetadata = serde_json::from_slice(&fs::read(&meta_path)?)?;
+        if meta.url != url {
+            debug!("Cache key collision for {}", url);
+            return Ok(None);
+        }
+        Ok(Some(CacheEntry {
+            body: fs::read(&body_path)?,
+            etag: meta.etag,
+            last_modified: meta.last_modified,
+            fetched_at: UNIX_EPOCH + Duration::from_secs(meta.fetched_at),
+        }))
+    }
+
+    pub fn store(
+        &self,
+        url: &str,
+        headers: &HeaderMap,
+        body: &[u8],
+    ) -> Result<(), FundamentalError> {
+        let header_str = |name| {
+            headers
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .map(str::to_owned)
+        };
+        let meta = CacheMetadata {
+            url: url.to_owned(),
+            etag: header_str(ETAG),
+            last_modified: header_str(LAST_MODIFIED),
+            fetched_at: Self::now(),
+        };
+        fs::create_dir_all(&self.dir)?;
+        let (body_path, meta_path) = self.paths(url);
+        fs::write(body_path, body)?;
+        fs::write(meta_path, serde_json::to_vec(&meta)?)?;
+        Ok(())
+    }
+
+    /// Mark an entry as revalidated (server answered 304 Not Modified)
+    pub fn touch(&self, url: &str) -> Result<(), FundamentalError> {
+        let (_, meta_path) = self.paths(url);
+        let mut meta: CacheMetadata = serde_json::from_slice(&fs::read(&meta_path)?)?;
+        meta.fetched_at = Self::now();
+        fs::write(meta_path, serde_json::to_vec(&meta)?)?;
+        Ok(())
+    }
+
+    fn paths(&self, url: &str) -> (PathBuf, PathBuf) {
+        let key = Self::key(url);
+        (
+            self.dir.join(format!("{}.body", key)),
+            self.dir.join(format!("{}.meta.json", key)),
+        )
+    }
+
+    /// Stable FNV-1a hash of the URL
+    fn key(url: &str) -> String {
+        let mut hash: u64 = 0xcbf29ce484222325;
+        for byte in url.bytes() {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(0x100000001b3);
+        }
+        format!("{:016x}", hash)
+    }
+
+    fn now() -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default()
+    }
+}
+
+// --- Test ---
+#[cfg(test)]
+mod unittests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+
+    const URL: &str = "https://data.sec.gov/api/xbrl/companyfacts/CIK0001045810.json";
+
+    #[test]
+    fn test_store_and_load() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = HttpCache::new(dir.path());
+        assert!(cache.load(URL).unwrap().is_none());
+
+        let mut headers = HeaderMap::new();
+        headers.insert(ETAG, HeaderValue::from_static("\"abc\""));
+        cache.store(URL, &headers, b"{}").unwrap();
+
+        let entry = cache.load(URL).unwrap().unwrap();
+        assert_eq!(entry.body, b"{}");
+        assert_eq!(entry.etag.as_deref(), Some("\"abc\""));
+        assert!(entry.last_modified.is_none());
+        assert!(cache.load("https://www.sec.gov/other").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_freshness() {
+        let entry = CacheEntry {
+            body: Vec::new(),
+            etag: None,
+            last_modified: None,
+            fetched_at: SystemTime::now() - Duration::from_secs(120),
+        };
+        assert!(entry.is_fresh(Some(Duration::from_secs(3600))));
+        assert!(!entry.is_fresh(Some(Duration::from_secs(60))));
+        assert!(!entry.is_fresh(None));
+    }
+
+    #[test]
+    fn test_touch_refreshes_entry() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = HttpCache::new(dir.path());
+        cache.store(URL, &HeaderMap::new(), b"body").unwrap();
+        cache.touch(URL).unwrap();
+        let entry = cache.load(URL).unwrap().unwrap();
+        assert!(entry.is_fresh(Some(Duration::from_secs(60))));
+    }
+}
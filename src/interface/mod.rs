@@ -1,33 +1,147 @@
-use log::debug;
-use reqwest::Client;
+pub mod cache;
+pub mod rate_limiter;
+pub mod retry;
+
+use crate::error::FundamentalError;
+use crate::interface::{cache::HttpCache, rate_limiter::RateLimiter, retry::RetryPolicy};
+
+use log::{debug, warn};
+use reqwest::header::{HeaderMap, IF_MODIFIED_SINCE, IF_NONE_MATCH};
+use reqwest::{Client, Response, StatusCode};
 use serde::de::DeserializeOwned;
 
+#[allow(async_fn_in_trait)]
 pub trait HttpClient<T> {
-    type Error: std::error::Error + Send + Sync + 'static + From<reqwest::Error>;
+    async fn fetch_data(&self) -> Result<T, FundamentalError>;
+
+    /// Retry policy applied to every request
+    fn retry_policy(&self) -> RetryPolicy {
+        RetryPolicy::default()
+    }
 
-    async fn fetch_data(&self) -> Result<T, Self::Error>;
+    /// On-disk response cache, disabled by default
+    fn cache(&self) -> Option<&HttpCache> {
+        None
+    }
 
     /// Fetch and convert json data to rust object
-    async fn fetch_json<RustObj: DeserializeOwned>(url: &str) -> Result<RustObj, Self::Error> {
-        let client = Self::create_client()?;
+    async fn fetch_json<RustObj: DeserializeOwned>(
+        &self,
+        url: &str,
+    ) -> Result<RustObj, FundamentalError> {
         debug!("Fetching json data {}", url);
-        let response = client.get(url).send().await?.error_for_status()?;
-        let json_data: RustObj = response.json().await?;
+        let body = self.fetch_bytes(url).await?;
+        let json_data: RustObj = serde_json::from_slice(&body)?;
         Ok(json_data)
     }
 
-    async fn fetch_xml(url: &str) -> Result<String, Self::Error> {
-        let client = Self::create_client()?;
+    async fn fetch_xml(&self, url: &str) -> Result<String, FundamentalError> {
         debug!("Fetching xml data {}", url);
-        let response = client.get(url).send().await?.error_for_status()?;
-        let xml_data = response.text().await?;
+        let body = self.fetch_bytes(url).await?;
+        let xml_data = String::from_utf8_lossy(&body).into_owned();
         Ok(xml_data)
     }
 
-    fn create_client() -> Result<Client, Self::Error> {
+    /// Fetch a response body, going through the cache when one is configured
+    async fn fetch_bytes(&self, url: &str) -> Result<Vec<u8>, FundamentalError> {
+        let Some(cache) = self.cache() else {
+            let response = self.send_request(url, HeaderMap::new()).await?;
+            return Ok(response.bytes().await?.to_vec());
+        };
+
+        let entry = cache.load(url)?;
+        let mut headers = HeaderMap::new();
+        match &entry {
+            Some(entry) if cache.offline_only || entry.is_fresh(cache.ttl) => {
+                debug!("Serving {} from cache", url);
+                return Ok(entry.body.clone());
+            }
+            Some(entry) => {
+                let validators = [
+                    (IF_NONE_MATCH, &entry.etag),
+                    (IF_MODIFIED_SINCE, &entry.last_modified),
+                ];
+                for (name, value) in validators {
+                    if let Some(value) = value.as_deref().and_then(|v| v.parse().ok()) {
+                        headers.insert(name, value);
+                    }
+                }
+            }
+            None if cache.offline_only => {
+                return Err(FundamentalError::NotCached {
+                    url: url.to_owned(),
+                });
+            }
+            None => {}
+        }
+
+        let response = self.send_request(url, headers).await?;
+        if let Some(entry) = entry
+            && response.status() == StatusCode::NOT_MODIFIED
+        {
+            debug!("{} not modified, serving from cache", url);
+            cache.touch(url)?;
+            return Ok(entry.body);
+        }
+        let response_headers = response.headers().clone();
+        let body = response.bytes().await?.to_vec();
+        cache.store(url, &response_headers, &body)?;
+        Ok(body)
+    }
+
+    /// Send a rate limited GET request, retrying transient failures
+    async fn send_request(
+        &self,
+        url: &str,
+        headers: HeaderMap,
+    ) -> Result<Response, FundamentalError> {
+        let client = self.create_client()?;
+        let policy = self.retry_policy();
+        let mut attempt = 1;
+        loop {
+            RateLimiter::global().acquire().await;
+            let request = client.get(url).headers(headers.clone());
+            let delay = match request.send().await {
+                Ok(response) => {
+                    let status = response.status();
+                    if !policy.should_retry_status(status, attempt) {
+                        if status == StatusCode::TOO_MANY_REQUESTS {
+                            return Err(FundamentalError::RateLimited {
+                                url: url.to_owned(),
+                                retry_after: RetryPolicy::parse_retry_after(response.headers()),
+                            });
+                        }
+                        return Ok(response.error_for_status()?);
+                    }
+                    let delay = policy.delay(attempt, Some(response.headers()));
+                    warn!(
+                        "{} returned {}, retrying in {:?} (attempt {}/{})",
+                        url, status, delay, attempt, policy.max_attempts
+                    );
+                    delay
+                }
+                Err(err) => {
+                    if !policy.should_retry_error(&err, attempt) {
+                        return Err(err.into());
+                    }
+                    let delay = policy.delay(attempt, None);
+                    warn!(
+                        "{} failed: {}, retrying in {:?} (attempt {}/{})",
+                        url, err, delay, attempt, policy.max_attempts
+                    );
+                    delay
+                }
+            };
+            tokio::time::sleep(delay).await;
+            attempt += 1;
+        }
+    }
+
+    /// HTTP client used for requests, implementors should return a shared client
+    fn create_client(&self) -> Result<Client, FundamentalError> {
         reqwest::Client::builder()
             .user_agent("(contact@example.com)")
             .build()
-            .map_err(Into::into)
+            .map_err(FundamentalError::from)
     }
 }
@@ -0,0 +1,139 @@
+use log::debug;
+use std::sync::OnceLock;
+use std::time::Duration;
+use tokio::sync::Mutex;
+use tokio::time::Instant;
+
+/// SEC fair-access policy: at most 10 requests per second per client
+pub const SEC_MAX_REQUESTS_PER_SECOND: u32 = 10;
+
+static GLOBAL_LIMITER: OnceLock<RateLimiter> = OnceLock::new();
+
+#[derive(Debug)]
+struct Bucket {
+    capacity: f64,
+    tokens: f64,
+    refill_per_sec: f64,
+    last_refill: Instant,
+}
+
+impl Bucket {
+    fn new(requests_per_second: u32) -> Self {
+        let rate = requests_per_second.max(1) as f64;
+        Self {
+            capacity: rate,
+            tokens: rate,
+            refill_per_sec: rate,
+            last_refill: Instant::now(),
+        }
+    }
+
+    fn refill(&mut self) {
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.last_refill).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.refill_per_sec).min(self.capacity);
+        self.last_refill = now;
+    }
+
+    /// Time until one token becomes available
+    fn wait_time(&self) -> Duration {
+        if self.tokens >= 1.0 {
+            return Duration::ZERO;
+        }
+        Duration::from_secs_f64((1.0 - self.tokens) / self.refill_per_sec)
+    }
+}
+
+/// Token-bucket rate limiter.
+/// Waiting requests are served in FIFO order, because the bucket is guarded
+/// by a fair mutex that is held while a request waits for its token.
+#[derive(Debug)]
+pub struct RateLimiter {
+    bucket: Mutex<Bucket>,
+}
+
+impl RateLimiter {
+    pub fn new(requests_per_second: u32) -> Self {
+        Self {
+            bucket: Mutex::new(Bucket::new(requests_per_second)),
+        }
+    }
+
+    /// Process-wide limiter shared by every SEC request
+    pub fn global() -> &'static RateLimiter {
+        GLOBAL_LIMITER.get_or_init(|| RateLimiter::new(SEC_MAX_REQUESTS_PER_SECOND))
+    }
+
+    /// Change the requests-per-second budget
+    pub async fn set_rate(&self, requests_per_second: u32) {
+        let mut bucket = self.bucket.lock().await;
+        *bucket = Bucket::new(requests_per_second);
+    }
+
+    /// Wait until a request is allowed to be sent
+    pub async fn acquire(&self) {
+        let mut bucket = self.bucket.lock().await;
+        bucket.refill();
+        let wait = bucket.wait_time();
+        if !wait.is_zero() {
+            debug!("Rate limit reached, waiting {:?}", wait);
+            tokio::time::sleep(wait).await;
+            bucket.refill();
+        }
+        bucket.tokens -= 1.0;
+    }
+}
+
+// --- Test ---
+#[cfg(test)]
+mod unittests {
+    use super::*;
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn test_burst_within_budget() {
+        let limiter = RateLimiter::new(5);
+        let start = std::time::Instant::now();
+        for _ in 0..5 {
+            limiter.acquire().await;
+        }
+        assert!(start.elapsed() < Duration::from_millis(100));
+    }
+
+    #[tokio::test]
+    async fn test_throttle_over_budget() {
+        let limiter = RateLimiter::new(10);
+        let start = std::time::Instant::now();
+        for _ in 0..15 {
+            limiter.acquire().await;
+        }
+        assert!(start.elapsed() >= Duration::from_millis(450));
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_requests_share_budget() {
+        let limiter = Arc::new(RateLimiter::new(10));
+        let start = std::time::Instant::now();
+        let tasks: Vec<_> = (0..20)
+            .map(|_| {
+                let limiter = Arc::clone(&limiter);
+                tokio::spawn(async move { limiter.acquire().await })
+            })
+            .collect();
+        for task in tasks {
+            task.await.unwrap();
+        }
+        assert!(start.elapsed() >= Duration::from_millis(950));
+    }
+
+    #[tokio::test]
+    async fn test_set_rate() {
+        let limiter = RateLimiter::new(1);
+        limiter.set_rate(20).await;
+        let start = std::time::Instant::now();
+        for _ in 0..20 {
+            limiter.acquire().await;
+        }
+        assert!(start.elapsed() < Duration::from_millis(100));
+    }
+}
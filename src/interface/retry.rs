@@ -0,0 +1,168 @@
+use chrono::{DateTime, Utc};
+use rand::Rng;
+use reqwest::StatusCode;
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+use std::time::Duration;
+
+/// Retry policy for SEC requests.
+/// Delays grow exponentially from `base_delay` up to `max_delay` with full jitter.
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// Total number of attempts, including the first one
+    pub max_attempts: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+    /// Response status codes that are worth another attempt
+    pub retryable_statuses: Vec<StatusCode>,
+    /// Wait for the delay given by the server's `Retry-After` header when present
+    pub respect_retry_after: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 5,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(30),
+            retryable_statuses: vec![
+                StatusCode::TOO_MANY_REQUESTS,
+                StatusCode::INTERNAL_SERVER_ERROR,
+                StatusCode::BAD_GATEWAY,
+                StatusCode::SERVICE_UNAVAILABLE,
+                StatusCode::GATEWAY_TIMEOUT,
+            ],
+            respect_retry_after: true,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Policy that gives up on the first failure
+    pub fn no_retry() -> Self {
+        Self {
+            max_attempts: 1,
+            ..Self::default()
+        }
+    }
+
+    pub fn should_retry_status(&self, status: StatusCode, attempt: u32) -> bool {
+        attempt < self.max_attempts && self.retryable_statuses.contains(&status)
+    }
+
+    /// Connection resets, timeouts and other transport failures are retryable
+    pub fn should_retry_error(&self, err: &reqwest::Error, attempt: u32) -> bool {
+        attempt < self.max_attempts && (err.is_connect() || err.is_timeout() || err.is_request())
+    }
+
+    /// Jittered exponential backoff for the given attempt (starting at 1)
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let exponent = attempt.saturating_sub(1).min(16);
+        let ceiling = self
+            .base_delay
+            .saturating_mul(1 << exponent)
+            .min(self.max_delay);
+        let ceiling_ms = ceiling.as_millis() as u64;
+        if ceiling_ms == 0 {
+            return Duration::ZERO;
+        }
+        Duration::from_millis(rand::thread_rng().gen_range(0..=ceiling_ms))
+    }
+
+    /// Delay before the next attempt, preferring the server's `Retry-After` header.
+    /// Never longer than `max_delay`, whatever the server asks for.
+    pub fn delay(&self, attempt: u32, headers: Option<&HeaderMap>) -> Duration {
+        if self.respect_retry_after
+            && let Some(retry_after) = headers.and_then(Self::parse_retry_after)
+        {
+            return retry_after.min(self.max_delay);
+        }
+        self.backoff(attempt)
+    }
+
+    /// `Retry-After` is either a number of seconds or an HTTP date
+    pub fn parse_retry_after(headers: &HeaderMap) -> Option<Duration> {
+        let value = headers.get(RETRY_AFTER)?.to_str().ok()?.trim();
+        if let Ok(seconds) = value.parse::<u64>() {
+            return Some(Duration::from_secs(seconds));
+        }
+        let date = DateTime::parse_from_rfc2822(value).ok()?;
+        let wait = date.with_timezone(&Utc) - Utc::now();
+        Some(wait.to_std().unwrap_or(Duration::ZERO))
+    }
+}
+
+// --- Test ---
+#[cfg(test)]
+mod unittests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+
+    #[test]
+    fn test_should_retry_status() {
+        let policy = RetryPolicy::default();
+        assert!(policy.should_retry_status(StatusCode::TOO_MANY_REQUESTS, 1));
+        assert!(policy.should_retry_status(StatusCode::SERVICE_UNAVAILABLE, 4));
+        assert!(!policy.should_retry_status(StatusCode::SERVICE_UNAVAILABLE, 5));
+        assert!(!policy.should_retry_status(StatusCode::NOT_FOUND, 1));
+        assert!(!RetryPolicy::no_retry().should_retry_status(StatusCode::TOO_MANY_REQUESTS, 1));
+    }
+
+    #[test]
+    fn test_backoff_is_bounded() {
+        let policy = RetryPolicy {
+            base_delay: Duration::from_millis(100),
+            max_delay: Duration::from_millis(1000),
+            ..RetryPolicy::default()
+        };
+        for attempt in 1..10 {
+            let ceiling = Duration::from_millis(100 * (1 << (attempt - 1))).min(policy.max_delay);
+            assert!(policy.backoff(attempt) <= ceiling);
+        }
+    }
+
+    #[test]
+    fn test_parse_retry_after_seconds() {
+        let mut headers = HeaderMap::new();
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("3"));
+        assert_eq!(
+            RetryPolicy::parse_retry_after(&headers),
+            Some(Duration::from_secs(3))
+        );
+        assert_eq!(
+            RetryPolicy::default().delay(1, Some(&headers)),
+            Duration::from_secs(3)
+        );
+    }
+
+    #[test]
+    fn test_retry_after_capped_by_max_delay() {
+        let mut headers = HeaderMap::new();
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("86400"));
+        let policy = RetryPolicy::default();
+        assert_eq!(policy.delay(1, Some(&headers)), policy.max_delay);
+    }
+
+    #[test]
+    fn test_parse_retry_after_http_date() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            RETRY_AFTER,
+            HeaderValue::from_static("Wed, 21 Oct 2015 07:28:00 GMT"),
+        );
+        assert_eq!(
+            RetryPolicy::parse_retry_after(&headers),
+            Some(Duration::ZERO)
+        );
+    }
+
+    #[test]
+    fn test_ignore_retry_after() {
+        let mut headers = HeaderMap::new();
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("120"));
+        let policy = RetryPolicy {
+            respect_retry_after: false,
+            ..RetryPolicy::default()
+        };
+        assert!(policy.delay(1, Some(&headers)) <= policy.base_delay);
+    }
+}
@@ -0,0 +1,8 @@
+pub mod common;
+pub mod error;
+pub mod financial_stmt;
+pub mod fx;
+pub mod interface;
+pub mod processor;
+pub mod ratios;
+pub mod store;
@@ -1,10 +1,5 @@
-mod common;
-mod financial_stmt;
-mod interface;
-mod processor;
-mod ratios;
-
-use crate::{
+use fundamental::{
+    error::FundamentalError,
     financial_stmt::{
         FinancialStatement, StatementHistory,
         balance_sheet::BalanceSheet,
@@ -13,6 +8,7 @@ use crate::{
         sec_client::{ConfiguredHttpClient, SecClient},
     },
     interface::HttpClient,
+    store::Store,
 };
 
 #[tokio::main]
@@ -20,7 +16,29 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     env_logger::init();
     let conf_client = ConfiguredHttpClient::new()?;
     let sec_client = SecClient::new(String::from("NVDA"), conf_client);
-    let json = sec_client.fetch_data().await?;
+
+    // Facts are downloaded once, later runs read them from the local store
+    let mut store = Store::open("fundamental.db")?;
+    if std::env::args().nth(1).as_deref() == Some("sync") {
+        let summary = store.sync(&sec_client).await?;
+        println!("{}", summary);
+        return Ok(());
+    }
+    let directory = sec_client.ticker_directory().await?;
+    let company = directory
+        .find_by_ticker("NVDA")
+        .ok_or("NVDA is not listed")?;
+    let json = match store.company_facts(company.cik) {
+        Ok(json) => json,
+        // Nothing stored for the company yet
+        Err(FundamentalError::MissingFacts) => {
+            let json = sec_client.fetch_data().await?;
+            store.upsert_company(company, None)?;
+            store.upsert_company_facts(company.cik, &json)?;
+            json
+        }
+        Err(err) => return Err(err.into()),
+    };
 
     let mut income_stmt = IncomeStatement::default();
     income_stmt.parse_quarly_latest(&json).expect("Err");
@@ -33,24 +51,18 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     let mut bs_history = StatementHistory::<BalanceSheet>::default();
     bs_history.fill_history(&json).expect("Err");
-    for rec in bs_history.records {
-        println!("{:?}", rec);
-        println!("-------")
-    }
+    store.save_history(company.cik, &bs_history)?;
+    println!("{}", serde_json::to_string_pretty(&bs_history)?);
 
     let mut ic_history = StatementHistory::<IncomeStatement>::default();
     ic_history.fill_history(&json).expect("Err");
-    for rec in ic_history.records {
-        println!("{:?}", rec);
-        println!("-------")
-    }
+    store.save_history(company.cik, &ic_history)?;
+    println!("{}", serde_json::to_string_pretty(&ic_history)?);
 
     let mut cf_history = StatementHistory::<CashFlow>::default();
     cf_history.fill_history(&json).expect("Err");
-    for rec in cf_history.records {
-        println!("{:?}", rec);
-        println!("-------")
-    }
+    store.save_history(company.cik, &cf_history)?;
+    println!("{}", serde_json::to_string_pretty(&cf_history)?);
 
     Ok(())
 }
@@ -1,8 +1,15 @@
-use crate::financial_stmt::sec_client::{CompanyTickers, SecClient};
+pub mod panel;
+
+use crate::error::FundamentalError;
+use crate::financial_stmt::{
+    bulk_archive::BulkArchiveSource,
+    sec_client::{CompanyTickers, SecClient},
+};
 use futures::stream::{self, StreamExt};
-use log::debug;
+use log::{debug, warn};
 use quick_xml::{Reader, events::Event};
 use std::collections::HashMap;
+use std::io::{Read, Seek};
 
 #[derive(Debug, Default, Clone)]
 pub struct Processor {
@@ -13,35 +20,67 @@ pub struct Processor {
 
 impl Processor {
     const MAX_CONCURRENT_REQUESTS: usize = 8;
-    pub async fn map_company_by_industry(&mut self) -> Result<(), Box<dyn std::error::Error>> {
-        let sec_client = SecClient::default();
+    pub async fn map_company_by_industry(
+        &mut self,
+        sec_client: &SecClient,
+    ) -> Result<(), FundamentalError> {
         let company_tickers: HashMap<String, CompanyTickers> =
             sec_client.fetch_all_company_tickers().await?;
+        // Load the ticker-to-CIK table once, every per-company client shares it
+        sec_client.ticker_directory().await?;
         let results = stream::iter(company_tickers.values())
-            .map(|ticker| async move {
-                let mut client = SecClient::default();
-                client.set_ticker(ticker.ticker.as_ref().cloned().unwrap_or_default());
-                debug!("Fetching {} SIC code", ticker.ticker.as_ref().unwrap());
-                let res = client.fetch_sic().await;
-                (ticker, res)
+            .map(|ticker| {
+                let mut client = sec_client.clone();
+                async move {
+                    client.set_ticker(ticker.ticker.as_ref().cloned().unwrap_or_default());
+                    debug!(
+                        "Fetching {} SIC code",
+                        ticker.ticker.as_deref().unwrap_or("<none>")
+                    );
+                    let res = client.fetch_sic().await;
+                    (ticker, res)
+                }
             })
             .buffer_unordered(Self::MAX_CONCURRENT_REQUESTS)
             .collect::<Vec<_>>()
             .await;
         for (ticker, res) in results {
-            if let Ok(xml) = res {
-                if let Ok(Some(sic)) = Self::extract_sic(&xml) {
-                    self.company_industry_mapping
-                        .entry(sic)
-                        .or_default()
-                        .push(ticker.cik_str);
+            if let Ok(xml) = res
+                && let Ok(Some(sic)) = Self::extract_sic(&xml)
+            {
+                self.company_industry_mapping
+                    .entry(sic)
+                    .or_default()
+                    .push(ticker.cik_str);
+            }
+        }
+        Ok(())
+    }
+
+    /// Map companies by industry from the bulk `submissions.zip` archive, without network access
+    pub fn map_company_by_industry_offline<R: Read + Seek>(
+        &mut self,
+        source: &mut BulkArchiveSource<R>,
+    ) -> Result<(), FundamentalError> {
+        for cik in source.submission_ciks() {
+            let submissions = match source.submissions(cik) {
+                Ok(json) => json,
+                Err(err) => {
+                    warn!("Skipping CIK {}: {}", cik, err);
+                    continue;
                 }
+            };
+            if let Some(sic) = submissions["sic"].as_str().filter(|sic| !sic.is_empty()) {
+                self.company_industry_mapping
+                    .entry(sic.to_owned())
+                    .or_default()
+                    .push(cik);
             }
         }
         Ok(())
     }
 
-    fn extract_sic(xml: &str) -> Result<Option<String>, Box<dyn std::error::Error>> {
+    fn extract_sic(xml: &str) -> Result<Option<String>, FundamentalError> {
         let mut reader = Reader::from_str(xml);
         reader.trim_text(true);
         let mut buf = Vec::new();
@@ -0,0 +1,330 @@
+use crate::error::FundamentalError;
+use crate::financial_stmt::{FinancialStatement, StatementHistory, sec_client::SecClient};
+use crate::interface::HttpClient;
+use crate::processor::Processor;
+
+use futures::stream::{self, StreamExt};
+use log::warn;
+
+/// Statement history of one company, tagged for cross-company panels
+#[derive(Debug, Default)]
+pub struct CompanyHistory<T> {
+    /// SEC Central index key (CIK)
+    pub cik: u32,
+    pub ticker: Option<String>,
+    /// SEC Standard industry code (SIC)
+    pub sic: String,
+    pub history: StatementHistory<T>,
+}
+
+impl Processor {
+    /// Fetch and parse the history of every company in `company_industry_mapping`.
+    /// Companies without a ticker or whose facts fail to load are logged and skipped.
+    pub async fn collect_histories<T: FinancialStatement>(
+        &self,
+        sec_client: &SecClient,
+    ) -> Result<Vec<CompanyHistory<T>>, FundamentalError> {
+        let directory = sec_client.ticker_directory().await?;
+        let companies: Vec<(u32, String, &String)> = self
+            .company_industry_mapping
+            .iter()
+            .flat_map(|(sic, ciks)| ciks.iter().map(move |cik| (*cik, sic)))
+            .filter_map(|(cik, sic)| {
+                let ticker = directory.find_by_cik(cik)?.ticker.clone()?;
+                Some((cik, ticker, sic))
+            })
+            .collect();
+        let results = stream::iter(companies)
+            .map(|(cik, ticker, sic)| {
+                let mut client = sec_client.clone();
+                async move {
+                    client.set_ticker(ticker.clone());
+                    let res = client.fetch_data().await.and_then(|json| {
+                        let mut history = StatementHistory::<T>::default();
+                        history.fill_history(&json)?;
+                        Ok(history)
+                    });
+                    (cik, ticker, sic, res)
+                }
+            })
+            .buffer_unordered(Self::MAX_CONCURRENT_REQUESTS)
+            .collect::<Vec<_>>()
+            .await;
+
+        let mut histories = Vec::with_capacity(results.len());
+        for (cik, ticker, sic, res) in results {
+            match res {
+                Ok(history) => histories.push(CompanyHistory {
+                    cik,
+                    ticker: Some(ticker),
+                    sic: sic.clone(),
+                    history,
+                }),
+                Err(err) => warn!("Skipping CIK {} ({}): {}", cik, ticker, err),
+            }
+        }
+        histories.sort_by(|a, b| (&a.sic, a.cik).cmp(&(&b.sic, b.cik)));
+        Ok(histories)
+    }
+}
+
+#[cfg(feature = "parquet")]
+pub use columnar::write_parquet;
+
+#[cfg(feature = "parquet")]
+mod columnar {
+    use super::CompanyHistory;
+    use crate::common::{Taxonomy, UnitKind};
+    use crate::error::FundamentalError;
+    use crate::financial_stmt::FinancialStatement;
+
+    use arrow_array::builder::{
+        Date32Builder, Decimal128Builder, Int64Builder, StringBuilder, UInt32Builder,
+    };
+    use arrow_array::{ArrayRef, RecordBatch, types::Date32Type};
+    use arrow_schema::{DataType, Field, Schema};
+    use parquet::arrow::ArrowWriter;
+    use parquet::errors::ParquetError;
+    use rust_decimal::Decimal;
+    use std::io::Write;
+    use std::sync::Arc;
+
+    /// Amounts are stored as DECIMAL(38, 6), per-share values need more than cents
+    const AMOUNT_PRECISION: u8 = 38;
+    const AMOUNT_SCALE: i8 = 6;
+
+    enum FieldColumn {
+        Amount(Decimal128Builder),
+        Count(Int64Builder),
+    }
+
+    /// Write the histories of many companies as one Parquet file, one row per company and
+    /// period. Columns are `cik`, `ticker`, `sic`, `period_start`, `period_end`,
+    /// `fiscal_period`, `form`, `accn`, `currency`, then one column per statement field.
+    pub fn write_parquet<T: FinancialStatement, W: Write + Send>(
+        companies: &[CompanyHistory<T>],
+        writer: W,
+    ) -> Result<(), FundamentalError> {
+        // Same columns whatever the taxonomy of each company
+        let statement = T::default();
+        let mut mappings = Vec::new();
+        for taxonomy in [Taxonomy::UsGaap, Taxonomy::IfrsFull] {
+            for mapping in statement.get_concept_mappings(taxonomy) {
+                if !mappings.iter().any(|(field, _)| *field == mapping.field) {
+                    mappings.push((mapping.field, mapping.unit));
+                }
+            }
+        }
+
+        let mut schema = vec![
+            Field::new("cik", DataType::UInt32, false),
+            Field::new("ticker", DataType::Utf8, true),
+            Field::new("sic", DataType::Utf8, false),
+            Field::new("period_start", DataType::Date32, true),
+            Field::new("period_end", DataType::Date32, true),
+            Field::new("fiscal_period", DataType::Utf8, false),
+            Field::new("form", DataType::Utf8, false),
+            Field::new("accn", DataType::Utf8, false),
+            Field::new("currency", DataType::Utf8, true),
+        ];
+        let mut cik = UInt32Builder::new();
+        let mut ticker = StringBuilder::new();
+        let mut sic = StringBuilder::new();
+        let mut period_start = Date32Builder::new();
+        let mut period_end = Date32Builder::new();
+        let mut fiscal_period = StringBuilder::new();
+        let mut form = StringBuilder::new();
+        let mut accn = StringBuilder::new();
+        let mut currency = StringBuilder::new();
+        let mut fields: Vec<FieldColumn> = Vec::with_capacity(mappings.len());
+        for (field, unit) in &mappings {
+            if *unit == UnitKind::Shares {
+                schema.push(Field::new(*field, DataType::Int64, true));
+                fields.push(FieldColumn::Count(Int64Builder::new()));
+            } else {
+                let data_type = DataType::Decimal128(AMOUNT_PRECISION, AMOUNT_SCALE);
+                schema.push(Field::new(*field, data_type.clone(), true));
+                fields.push(FieldColumn::Amount(
+                    Decimal128Builder::new().with_data_type(data_type),
+                ));
+            }
+        }
+
+        for company in companies {
+            for record in &company.history.records {
+                for ((field, _), column) in mappings.iter().zip(fields.iter_mut()) {
+                    match column {
+                        FieldColumn::Amount(builder) => builder.append_option(
+                            record
+                                .get_field_value(field)
+                                .map(|money| to_decimal128(money.amount)),
+                        ),
+                        FieldColumn::Count(builder) => {
+                            builder.append_option(record.get_share_count(field))
+                        }
+                    }
+                }
+                let meta_data = record.meta_data();
+                cik.append_value(company.cik);
+                ticker.append_option(company.ticker.as_deref());
+                sic.append_value(&company.sic);
+                period_start.append_option(
+                    meta_data
+                        .period
+                        .and_then(|period| period.start())
+                        .map(Date32Type::from_naive_date),
+                );
+                period_end.append_option(
+                    meta_data
+                        .period
+                        .map(|period| Date32Type::from_naive_date(period.end())),
+                );
+                fiscal_period.append_value(meta_data.fiscal_period.as_str());
+                form.append_value(meta_data.form_report.as_str());
+                accn.append_value(&meta_data.accn);
+                currency.append_option(meta_data.currency.as_deref());
+            }
+        }
+
+        let mut columns: Vec<ArrayRef> = vec![
+            Arc::new(cik.finish()),
+            Arc::new(ticker.finish()),
+            Arc::new(sic.finish()),
+            Arc::new(period_start.finish()),
+            Arc::new(period_end.finish()),
+            Arc::new(fiscal_period.finish()),
+            Arc::new(form.finish()),
+            Arc::new(accn.finish()),
+            Arc::new(currency.finish()),
+        ];
+        for column in fields {
+            columns.push(match column {
+                FieldColumn::Amount(mut builder) => Arc::new(builder.finish()),
+                FieldColumn::Count(mut builder) => Arc::new(builder.finish()),
+            });
+        }
+        let schema = Arc::new(Schema::new(schema));
+        let batch =
+            RecordBatch::try_new(Arc::clone(&schema), columns).map_err(ParquetError::from)?;
+        let mut arrow_writer = ArrowWriter::try_new(writer, schema, None)?;
+        arrow_writer.write(&batch)?;
+        arrow_writer.close()?;
+        Ok(())
+    }
+
+    fn to_decimal128(amount: Decimal) -> i128 {
+        let mut amount = amount.round_dp(AMOUNT_SCALE as u32);
+        amount.rescale(AMOUNT_SCALE as u32);
+        amount.mantissa()
+    }
+
+    // --- Test ---
+    #[cfg(test)]
+    mod unittests {
+        use super::*;
+        use crate::common::HistoryMode;
+        use crate::financial_stmt::StatementHistory;
+        use crate::financial_stmt::cash_flow::CashFlow;
+        use crate::financial_stmt::income_statement::IncomeStatement;
+        use arrow_array::{
+            Array, Date32Array, Decimal128Array, Int64Array, StringArray, UInt32Array,
+        };
+        use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+        use serde_json::Value;
+
+        const FIXTURE: &str = include_str!("../../tests/fixtures/companyfacts_CIK0001045810.json");
+
+        fn company<T: FinancialStatement>(cik: u32, ticker: &str) -> CompanyHistory<T> {
+            let json_data: Value = serde_json::from_str(FIXTURE).unwrap();
+            let mut history = StatementHistory::<T> {
+                mode: HistoryMode::Quarterly,
+                ..Default::default()
+            };
+            history.fill_history(&json_data).unwrap();
+            CompanyHistory {
+                cik,
+                ticker: Some(ticker.to_owned()),
+                sic: String::from("3674"),
+                history,
+            }
+        }
+
+        #[test]
+        fn test_parquet_round_trip() {
+            let companies = vec![
+                company::<IncomeStatement>(1045810, "NVDA"),
+                company(2488, "AMD"),
+            ];
+            let rows_per_company = companies[0].history.records.len();
+            let mut file = tempfile::tempfile().unwrap();
+            write_parquet(&companies, &mut file).unwrap();
+
+            let reader = ParquetRecordBatchReaderBuilder::try_new(file)
+                .unwrap()
+                .build()
+                .unwrap();
+            let batches: Vec<RecordBatch> = reader.map(Result::unwrap).collect();
+            assert_eq!(batches.len(), 1);
+            let batch = &batches[0];
+            assert_eq!(batch.num_rows(), 2 * rows_per_company);
+            let schema = batch.schema();
+            let names: Vec<&str> = schema.fields().iter().map(|f| f.name().as_str()).collect();
+            assert_eq!(names[..4], ["cik", "ticker", "sic", "period_start"]);
+            assert!(names.contains(&"total_revenue"));
+
+            let cik = batch["cik"].as_any().downcast_ref::<UInt32Array>().unwrap();
+            assert_eq!(cik.value(0), 1045810);
+            assert_eq!(cik.value(rows_per_company), 2488);
+            let period_end = batch["period_end"]
+                .as_any()
+                .downcast_ref::<Date32Array>()
+                .unwrap();
+            assert_eq!(
+                period_end.value_as_date(0).unwrap().to_string(),
+                "2024-12-31"
+            );
+
+            let eps_basic = batch["eps_basic"]
+                .as_any()
+                .downcast_ref::<Decimal128Array>()
+                .unwrap();
+            // Q4 is derived and has no per-share data, Q3 reports 0.17
+            assert!(eps_basic.is_null(0));
+            assert_eq!(eps_basic.value_as_string(1), "0.170000");
+            let shares = batch["weighted_average_shares_basic"]
+                .as_any()
+                .downcast_ref::<Int64Array>()
+                .unwrap();
+            assert_eq!(shares.value(1), 244_000_000);
+        }
+
+        #[test]
+        fn test_parquet_cash_flow_periods() {
+            let companies = vec![company::<CashFlow>(1045810, "NVDA")];
+            let mut file = tempfile::tempfile().unwrap();
+            write_parquet(&companies, &mut file).unwrap();
+
+            let reader = ParquetRecordBatchReaderBuilder::try_new(file)
+                .unwrap()
+                .build()
+                .unwrap();
+            let batches: Vec<RecordBatch> = reader.map(Result::unwrap).collect();
+            let batch = &batches[0];
+            // Every quarter has the period of its flows, not of the cash position
+            let period_start = batch["period_start"]
+                .as_any()
+                .downcast_ref::<Date32Array>()
+                .unwrap();
+            assert_eq!(period_start.null_count(), 0);
+            let accn = batch["accn"]
+                .as_any()
+                .downcast_ref::<StringArray>()
+                .unwrap();
+            for (row, record) in companies[0].history.records.iter().enumerate() {
+                let source = &record.meta_data().sources["operating_cash_flow"];
+                assert_eq!(period_start.value_as_date(row), source.period.start());
+                assert_eq!(accn.value(row), source.accn);
+            }
+        }
+    }
+}
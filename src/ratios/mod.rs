@@ -77,6 +77,31 @@ impl Ratios {
         }
         net_income / total_revenue
     }
+
+    /// # Valuation
+    /// Market capitalization, e.g. from `ShareData::shares_outstanding`.
+    pub fn market_cap(price: f64, shares_outstanding: f64) -> f64 {
+        price * shares_outstanding
+    }
+
+    /// # Valuation
+    /// Price-to-earnings ratio, from diluted EPS preferably.
+    /// Negative earnings give a negative ratio.
+    pub fn price_to_earnings(price: f64, eps: f64) -> f64 {
+        if eps == 0.0 {
+            return 0.0;
+        }
+        price / eps
+    }
+
+    /// # Per share
+    /// Book value (total equity) per share outstanding.
+    pub fn book_value_per_share(total_equity: f64, shares_outstanding: f64) -> f64 {
+        if shares_outstanding == 0.0 {
+            return 0.0;
+        }
+        total_equity / shares_outstanding
+    }
 }
 
 // ---- Test ----
@@ -195,4 +220,33 @@ mod unittests {
         total_revenue = 0.0;
         assert_eq!(Ratios::gross_profit_margin(net_income, total_revenue), 0.0);
     }
+
+    #[test]
+    fn test_market_cap() {
+        assert_eq!(Ratios::market_cap(120.0, 1000.0), 120_000.0);
+    }
+
+    #[test]
+    fn test_price_to_earnings() {
+        let price = 120.0;
+        let mut eps = 2.5;
+        assert_eq!(Ratios::price_to_earnings(price, eps), (price / eps));
+        eps = 0.0;
+        assert_eq!(Ratios::price_to_earnings(price, eps), 0.0);
+    }
+
+    #[test]
+    fn test_book_value_per_share() {
+        let total_equity = 5000.0;
+        let mut shares_outstanding = 100.0;
+        assert_eq!(
+            Ratios::book_value_per_share(total_equity, shares_outstanding),
+            (total_equity / shares_outstanding)
+        );
+        shares_outstanding = 0.0;
+        assert_eq!(
+            Ratios::book_value_per_share(total_equity, shares_outstanding),
+            0.0
+        );
+    }
 }
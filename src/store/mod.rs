@@ -0,0 +1,576 @@
+pub mod sync;
+
+use crate::common::FiscalPeriod;
+use crate::error::FundamentalError;
+use crate::financial_stmt::{
+    FinancialStatement, StatementHistory, fact::Fact, sec_client::CompanyTickersExchange,
+};
+
+use log::{debug, warn};
+use rusqlite::{Connection, OptionalExtension, params};
+use serde::{Serialize, de::DeserializeOwned};
+use serde_json::{Map, Value, json};
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::path::Path;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS companies (
+    cik INTEGER PRIMARY KEY,
+    ticker TEXT,
+    name TEXT,
+    exchange TEXT,
+    sic TEXT
+);
+CREATE TABLE IF NOT EXISTS facts (
+    cik INTEGER NOT NULL,
+    taxonomy TEXT NOT NULL,
+    concept TEXT NOT NULL,
+    unit TEXT NOT NULL,
+    period_start TEXT,
+    period_end TEXT NOT NULL,
+    val TEXT NOT NULL,
+    accn TEXT NOT NULL,
+    fy INTEGER,
+    fp TEXT,
+    form TEXT NOT NULL,
+    filed TEXT,
+    frame TEXT
+);
+CREATE INDEX IF NOT EXISTS facts_by_accn ON facts (cik, accn);
+CREATE TABLE IF NOT EXISTS statements (
+    cik INTEGER NOT NULL,
+    kind TEXT NOT NULL,
+    fiscal_period TEXT NOT NULL,
+    fiscal_year INTEGER,
+    period_start TEXT,
+    period_end TEXT NOT NULL,
+    accn TEXT NOT NULL,
+    body TEXT NOT NULL,
+    PRIMARY KEY (cik, kind, fiscal_period, period_end)
+);
+CREATE TABLE IF NOT EXISTS sync_cursor (
+    cik INTEGER PRIMARY KEY,
+    last_filed TEXT NOT NULL,
+    synced_at TEXT NOT NULL
+);
+";
+
+/// Filter of `Store::load_statements`, unset fields match everything
+#[derive(Debug, Default, Clone)]
+pub struct StatementQuery {
+    pub cik: Option<u32>,
+    /// SEC Standard industry code (SIC) of the company
+    pub sic: Option<String>,
+    /// Fiscal year as labelled by the filer, e.g. 2025 for a year ending in January 2025
+    pub fiscal_year: Option<i32>,
+    pub fiscal_period: Option<FiscalPeriod>,
+}
+
+/// Local SQLite store of companies, raw company facts and parsed statements.
+/// Statements are stored as their serde JSON, keyed by `FinancialStatement::NAME`.
+pub struct Store {
+    conn: Connection,
+}
+
+impl Store {
+    /// Open the store at `path`, creating the file and tables if needed
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, FundamentalError> {
+        debug!("Opening store {}", path.as_ref().display());
+        Self::new(Connection::open(path)?)
+    }
+
+    /// Store living in memory only, dropped with it
+    pub fn open_in_memory() -> Result<Self, FundamentalError> {
+        Self::new(Connection::open_in_memory()?)
+    }
+
+    fn new(conn: Connection) -> Result<Self, FundamentalError> {
+        conn.execute_batch(SCHEMA)?;
+        Ok(Self { conn })
+    }
+
+    /// Insert or update a company, `sic` is kept when `None`
+    pub fn upsert_company(
+        &self,
+        company: &CompanyTickersExchange,
+        sic: Option<&str>,
+    ) -> Result<(), FundamentalError> {
+        self.conn.execute(
+            "INSERT INTO companies (cik, ticker, name, exchange, sic) VALUES (?1, ?2, ?3, ?4, ?5)
+             ON CONFLICT (cik) DO UPDATE SET ticker = ?2, name = ?3, exchange = ?4,
+                 sic = coalesce(?5, sic)",
+            params![
+                company.cik,
+                company.ticker,
+                company.name,
+                company.exchange,
+                sic
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Every stored company, by CIK
+    pub fn companies(&self) -> Result<Vec<CompanyTickersExchange>, FundamentalError> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT cik, name, ticker, exchange FROM companies ORDER BY cik")?;
+        let companies = stmt
+            .query_map([], |row| {
+                Ok(CompanyTickersExchange {
+                    cik: row.get(0)?,
+                    name: row.get(1)?,
+                    ticker: row.get(2)?,
+                    exchange: row.get(3)?,
+                })
+            })?
+            .collect::<Result<_, _>>()?;
+        Ok(companies)
+    }
+
+    /// SIC of a stored company, `None` when unknown
+    pub fn company_sic(&self, cik: u32) -> Result<Option<String>, FundamentalError> {
+        let sic = self
+            .conn
+            .query_row(
+                "SELECT sic FROM companies WHERE cik = ?1",
+                params![cik],
+                |row| row.get(0),
+            )
+            .optional()?;
+        Ok(sic.flatten())
+    }
+
+    /// Accession numbers of every filing with facts stored for the company
+    pub fn accessions(&self, cik: u32) -> Result<HashSet<String>, FundamentalError> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT DISTINCT accn FROM facts WHERE cik = ?1")?;
+        let accessions = stmt
+            .query_map(params![cik], |row| row.get(0))?
+            .collect::<Result<_, _>>()?;
+        Ok(accessions)
+    }
+
+    /// Store the facts of `SecClient::fetch_data` JSON, one filing at a time.
+    /// Filings are immutable, so facts of accession numbers already stored are left
+    /// untouched and only new filings are written. Malformed facts are logged and skipped.
+    /// Returns the new accession numbers.
+    pub fn upsert_company_facts(
+        &mut self,
+        cik: u32,
+        json_data: &Value,
+    ) -> Result<Vec<String>, FundamentalError> {
+        let taxonomies = json_data["facts"]
+            .as_object()
+            .ok_or(FundamentalError::MissingFacts)?;
+        let stored = self.accessions(cik)?;
+        // Kept in the order of the JSON, which the parsers rely on for ties
+        let mut new_facts: Vec<(&str, &str, &str, Fact)> = Vec::new();
+        let mut new_accessions = BTreeSet::new();
+        for (taxonomy, concepts) in taxonomies {
+            for (concept, data) in concepts.as_object().into_iter().flatten() {
+                for (unit, facts) in data["units"].as_object().into_iter().flatten() {
+                    for value in facts.as_array().into_iter().flatten() {
+                        let fact = match Fact::from_value(value) {
+                            Ok(fact) => fact,
+                            Err(reason) => {
+                                warn!("Skipping fact of {}: {}", concept, reason);
+                                continue;
+                            }
+                        };
+                        if !stored.contains(&fact.accn) {
+                            new_accessions.insert(fact.accn.clone());
+                            new_facts.push((taxonomy, concept, unit, fact));
+                        }
+                    }
+                }
+            }
+        }
+
+        let tx = self.conn.transaction()?;
+        {
+            let mut insert = tx.prepare(
+                "INSERT INTO facts (cik, taxonomy, concept, unit, period_start, period_end, val,
+                     accn, fy, fp, form, filed, frame)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)",
+            )?;
+            for (taxonomy, concept, unit, fact) in &new_facts {
+                insert.execute(params![
+                    cik,
+                    taxonomy,
+                    concept,
+                    unit,
+                    fact.start.map(|start| start.to_string()),
+                    fact.end.to_string(),
+                    fact.val.to_string(),
+                    fact.accn,
+                    fact.fy,
+                    fact.fp,
+                    fact.form,
+                    fact.filed.map(|filed| filed.to_string()),
+                    fact.frame,
+                ])?;
+            }
+        }
+        tx.commit()?;
+        debug!("Stored {} new filings of CIK {}", new_accessions.len(), cik);
+        Ok(new_accessions.into_iter().collect())
+    }
+
+    /// Company facts of one company rebuilt from the store, same shape as
+    /// `SecClient::fetch_data`, so every `FinancialStatement` parser can read it
+    pub fn company_facts(&self, cik: u32) -> Result<Value, FundamentalError> {
+        let mut stmt = self.conn.prepare(
+            "SELECT taxonomy, concept, unit, period_start, period_end, val, accn, fy, fp, form,
+                 filed, frame
+             FROM facts WHERE cik = ?1 ORDER BY rowid",
+        )?;
+        let mut rows = stmt.query(params![cik])?;
+        // taxonomy -> concept -> unit -> facts
+        let mut taxonomies: BTreeMap<String, BTreeMap<String, BTreeMap<String, Vec<Value>>>> =
+            BTreeMap::new();
+        while let Some(row) = rows.next()? {
+            let val: String = row.get(5)?;
+            let mut fact = Map::new();
+            if let Some(start) = row.get::<_, Option<String>>(3)? {
+                fact.insert(String::from("start"), Value::from(start));
+            }
+            fact.insert(String::from("end"), Value::from(row.get::<_, String>(4)?));
+            // Written from a Decimal, so always a valid JSON number
+            fact.insert(String::from("val"), serde_json::from_str(&val)?);
+            fact.insert(String::from("accn"), Value::from(row.get::<_, String>(6)?));
+            fact.insert(
+                String::from("fy"),
+                Value::from(row.get::<_, Option<i32>>(7)?),
+            );
+            fact.insert(
+                String::from("fp"),
+                Value::from(row.get::<_, Option<String>>(8)?),
+            );
+            fact.insert(String::from("form"), Value::from(row.get::<_, String>(9)?));
+            fact.insert(
+                String::from("filed"),
+                Value::from(row.get::<_, Option<String>>(10)?),
+            );
+            if let Some(frame) = row.get::<_, Option<String>>(11)? {
+                fact.insert(String::from("frame"), Value::from(frame));
+            }
+            taxonomies
+                .entry(row.get(0)?)
+                .or_default()
+                .entry(row.get(1)?)
+                .or_default()
+                .entry(row.get(2)?)
+                .or_default()
+                .push(Value::Object(fact));
+        }
+        if taxonomies.is_empty() {
+            return Err(FundamentalError::MissingFacts);
+        }
+        let facts: Map<String, Value> = taxonomies
+            .into_iter()
+            .map(|(taxonomy, concepts)| {
+                let concepts: Map<String, Value> = concepts
+                    .into_iter()
+                    .map(|(concept, units)| (concept, json!({ "units": units })))
+                    .collect();
+                (taxonomy, Value::Object(concepts))
+            })
+            .collect();
+        let entity_name: Option<String> = self
+            .conn
+            .query_row(
+                "SELECT name FROM companies WHERE cik = ?1",
+                params![cik],
+                |row| row.get(0),
+            )
+            .optional()?
+            .flatten();
+        Ok(json!({
+            "cik": cik,
+            "entityName": entity_name,
+            "facts": facts,
+        }))
+    }
+
+    /// Insert or replace one parsed statement, skipped when it has no period
+    pub fn save_statement<T: FinancialStatement + Serialize>(
+        &self,
+        cik: u32,
+        statement: &T,
+    ) -> Result<(), FundamentalError> {
+        Self::insert_statement(&self.conn, cik, statement)
+    }
+
+    /// Insert or replace every record of a history in one transaction
+    pub fn save_history<T: FinancialStatement + Serialize>(
+        &mut self,
+        cik: u32,
+        history: &StatementHistory<T>,
+    ) -> Result<(), FundamentalError> {
+        let tx = self.conn.transaction()?;
+        for record in &history.records {
+            Self::insert_statement(&tx, cik, record)?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Stored statements matching `query`, by CIK and most recent period first
+    pub fn load_statements<T: FinancialStatement + DeserializeOwned>(
+        &self,
+        query: &StatementQuery,
+    ) -> Result<Vec<(u32, T)>, FundamentalError> {
+        let mut stmt = self.conn.prepare(
+            "SELECT s.cik, s.body FROM statements s LEFT JOIN companies c ON c.cik = s.cik
+             WHERE s.kind = ?1
+                 AND (?2 IS NULL OR s.cik = ?2)
+                 AND (?3 IS NULL OR c.sic = ?3)
+                 AND (?4 IS NULL OR s.fiscal_year = ?4)
+                 AND (?5 IS NULL OR s.fiscal_period = ?5)
+             ORDER BY s.cik, s.period_end DESC, s.fiscal_period",
+        )?;
+        let rows = stmt.query_map(
+            params![
+                T::NAME,
+                query.cik,
+                query.sic,
+                query.fiscal_year,
+                query.fiscal_period.map(|fp| fp.as_str()),
+            ],
+            |row| Ok((row.get::<_, u32>(0)?, row.get::<_, String>(1)?)),
+        )?;
+        let mut statements = Vec::new();
+        for row in rows {
+            let (cik, body) = row?;
+            statements.push((cik, serde_json::from_str(&body)?));
+        }
+        Ok(statements)
+    }
+
+    fn insert_statement<T: FinancialStatement + Serialize>(
+        conn: &Connection,
+        cik: u32,
+        statement: &T,
+    ) -> Result<(), FundamentalError> {
+        let body = serde_json::to_string(statement)?;
+        let meta_data = statement.meta_data();
+        let Some(period) = meta_data.period else {
+            return Ok(());
+        };
+        conn.execute(
+            "INSERT INTO statements (cik, kind, fiscal_period, fiscal_year, period_start,
+                 period_end, accn, body)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+             ON CONFLICT (cik, kind, fiscal_period, period_end) DO UPDATE SET
+                 fiscal_year = ?4, period_start = ?5, accn = ?7, body = ?8",
+            params![
+                cik,
+                T::NAME,
+                meta_data.fiscal_period.as_str(),
+                meta_data.fiscal_year,
+                period.start().map(|start| start.to_string()),
+                period.end().to_string(),
+                meta_data.accn,
+                body,
+            ],
+        )?;
+        Ok(())
+    }
+}
+
+// --- Test ---
+#[cfg(test)]
+mod unittests {
+    use super::*;
+    use crate::common::HistoryMode;
+    use crate::financial_stmt::{
+        balance_sheet::BalanceSheet, cash_flow::CashFlow, income_statement::IncomeStatement,
+    };
+
+    const FIXTURE: &str = include_str!("../../tests/fixtures/companyfacts_CIK0001045810.json");
+    const NVDA: u32 = 1045810;
+    const JANUARY_FIXTURE: &str =
+        include_str!("../../tests/fixtures/companyfacts_fiscal_year_january.json");
+
+    fn nvidia() -> CompanyTickersExchange {
+        CompanyTickersExchange {
+            cik: NVDA,
+            name: Some(String::from("NVIDIA CORP")),
+            ticker: Some(String::from("NVDA")),
+            exchange: Some(String::from("Nasdaq")),
+        }
+    }
+
+    fn quarterly_history<T: FinancialStatement>(json_data: &Value) -> StatementHistory<T> {
+        let mut history = StatementHistory::<T> {
+            mode: HistoryMode::Quarterly,
+            ..Default::default()
+        };
+        history.fill_history(json_data).unwrap();
+        history
+    }
+
+    #[test]
+    fn test_upsert_company_facts_incremental() {
+        let json_data: Value = serde_json::from_str(FIXTURE).unwrap();
+        let mut store = Store::open_in_memory().unwrap();
+        let accessions = store.upsert_company_facts(NVDA, &json_data).unwrap();
+        assert!(accessions.contains(&String::from("0001045810-25-000005")));
+        assert_eq!(store.accessions(NVDA).unwrap().len(), accessions.len());
+        // Nothing new the second time
+        assert!(
+            store
+                .upsert_company_facts(NVDA, &json_data)
+                .unwrap()
+                .is_empty()
+        );
+        assert!(matches!(
+            store.company_facts(320193),
+            Err(FundamentalError::MissingFacts)
+        ));
+    }
+
+    #[test]
+    fn test_parse_from_stored_facts() {
+        let json_data: Value = serde_json::from_str(FIXTURE).unwrap();
+        let mut store = Store::open_in_memory().unwrap();
+        store.upsert_company(&nvidia(), Some("3674")).unwrap();
+        store.upsert_company_facts(NVDA, &json_data).unwrap();
+        let stored = store.company_facts(NVDA).unwrap();
+        assert_eq!(stored["entityName"], "NVIDIA CORP");
+
+        let live = quarterly_history::<IncomeStatement>(&json_data);
+        let from_store = quarterly_history::<IncomeStatement>(&stored);
+        assert_eq!(
+            serde_json::to_value(&from_store).unwrap(),
+            serde_json::to_value(&live).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_load_statements_by_industry_and_year() {
+        let json_data: Value = serde_json::from_str(FIXTURE).unwrap();
+        let mut store = Store::open_in_memory().unwrap();
+        store.upsert_company(&nvidia(), Some("3674")).unwrap();
+        // SIC is kept when the update does not know it
+        store.upsert_company(&nvidia(), None).unwrap();
+        assert_eq!(store.company_sic(NVDA).unwrap().as_deref(), Some("3674"));
+
+        let history = quarterly_history::<BalanceSheet>(&json_data);
+        store.save_history(NVDA, &history).unwrap();
+        // Saving again replaces the records
+        store.save_history(NVDA, &history).unwrap();
+
+        let query = StatementQuery {
+            sic: Some(String::from("3674")),
+            fiscal_year: Some(2023),
+            ..Default::default()
+        };
+        let mut balance_sheets = store.load_statements::<BalanceSheet>(&query).unwrap();
+        let ends: Vec<_> = balance_sheets
+            .iter_mut()
+            .map(|(cik, record)| (*cik, record.get_metadata().period.unwrap().end()))
+            .map(|(cik, end)| (cik, end.to_string()))
+            .collect();
+        assert_eq!(
+            ends,
+            [
+                (NVDA, String::from("2023-12-31")),
+                (NVDA, String::from("2023-09-30")),
+                (NVDA, String::from("2023-06-30")),
+                (NVDA, String::from("2023-03-31")),
+            ]
+        );
+        assert_eq!(
+            balance_sheets[0].1.total_assets,
+            history.records[4].total_assets
+        );
+
+        let query = StatementQuery {
+            sic: Some(String::from("3571")),
+            ..Default::default()
+        };
+        assert!(
+            store
+                .load_statements::<BalanceSheet>(&query)
+                .unwrap()
+                .is_empty()
+        );
+        // Other statements are stored under their own name
+        let query = StatementQuery::default();
+        assert!(
+            store
+                .load_statements::<IncomeStatement>(&query)
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_save_cash_flow_period() {
+        let json_data: Value = serde_json::from_str(FIXTURE).unwrap();
+        let mut store = Store::open_in_memory().unwrap();
+        let history = quarterly_history::<CashFlow>(&json_data);
+        store.save_history(NVDA, &history).unwrap();
+
+        let mut stmt = store
+            .conn
+            .prepare("SELECT period_start, accn FROM statements ORDER BY period_end DESC")
+            .unwrap();
+        let rows: Vec<(Option<String>, String)> = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(rows.len(), history.records.len());
+        // Period of the flows, not of the cash position
+        for ((period_start, accn), record) in rows.into_iter().zip(&history.records) {
+            let source = &record.meta_data().sources["operating_cash_flow"];
+            assert_eq!(period_start, source.period.start().map(|d| d.to_string()));
+            assert_eq!(accn, source.accn);
+        }
+    }
+
+    #[test]
+    fn test_load_statements_by_fiscal_year_ending_in_january() {
+        let json_data: Value = serde_json::from_str(JANUARY_FIXTURE).unwrap();
+        let mut store = Store::open_in_memory().unwrap();
+        let quarterly = quarterly_history::<BalanceSheet>(&json_data);
+        store.save_history(1, &quarterly).unwrap();
+        let mut annual = StatementHistory::<BalanceSheet>::default();
+        annual.fill_history(&json_data).unwrap();
+        store.save_history(1, &annual).unwrap();
+
+        let period_ends = |query: &StatementQuery| -> Vec<String> {
+            let mut statements = store.load_statements::<BalanceSheet>(query).unwrap();
+            statements
+                .iter_mut()
+                .map(|(_, record)| record.get_metadata().period.unwrap().end().to_string())
+                .collect()
+        };
+        // Fiscal 2025 runs from February 2024 to January 2025, its end is both FY and Q4
+        let query = StatementQuery {
+            fiscal_year: Some(2025),
+            ..Default::default()
+        };
+        assert_eq!(
+            period_ends(&query),
+            [
+                "2025-01-26",
+                "2025-01-26",
+                "2024-10-27",
+                "2024-07-28",
+                "2024-04-28"
+            ]
+        );
+        // Comparatives of later annual reports keep their own fiscal year
+        let query = StatementQuery {
+            fiscal_year: Some(2024),
+            fiscal_period: Some(FiscalPeriod::FY),
+            ..Default::default()
+        };
+        assert_eq!(period_ends(&query), ["2024-01-28"]);
+    }
+}
@@ -0,0 +1,213 @@
+use crate::common::FormReport;
+use crate::error::FundamentalError;
+use crate::financial_stmt::sec_client::SecClient;
+use crate::store::Store;
+
+use chrono::{NaiveDate, Utc};
+use futures::stream::{self, StreamExt};
+use log::{debug, warn};
+use rusqlite::{OptionalExtension, params};
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// Columns of submissions `filings.recent` read by the sync, one entry per filing
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RecentFilings {
+    accession_number: Vec<String>,
+    filing_date: Vec<NaiveDate>,
+    form: Vec<String>,
+}
+
+/// Filing listed in company submissions
+#[derive(Debug, Clone, PartialEq)]
+pub struct Filing {
+    pub accn: String,
+    pub form: FormReport,
+    pub filed: NaiveDate,
+}
+
+/// Outcome of `Store::sync`
+#[derive(Debug, Default)]
+pub struct SyncSummary {
+    /// Number of companies whose submissions were read
+    pub checked: usize,
+    /// New annual and quarterly reports of each company whose facts were stored
+    pub updated: BTreeMap<u32, Vec<Filing>>,
+    /// New original reports whose facts are not in company facts yet, which lags
+    /// submissions. The next sync looks at them again.
+    pub pending: BTreeMap<u32, Vec<Filing>>,
+    /// Companies that could not be synced, with the reason
+    pub failed: BTreeMap<u32, String>,
+}
+
+impl fmt::Display for SyncSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Checked {} companies: {} updated, {} pending, {} failed",
+            self.checked,
+            self.updated.len(),
+            self.pending.len(),
+            self.failed.len()
+        )?;
+        for (cik, filings) in &self.updated {
+            for filing in filings {
+                write!(
+                    f,
+                    "\n  CIK {}: {} {} filed {}",
+                    cik,
+                    filing.form.as_str(),
+                    filing.accn,
+                    filing.filed
+                )?;
+            }
+        }
+        for (cik, filings) in &self.pending {
+            for filing in filings {
+                write!(
+                    f,
+                    "\n  CIK {}: {} {} filed {} not in company facts yet",
+                    cik,
+                    filing.form.as_str(),
+                    filing.accn,
+                    filing.filed
+                )?;
+            }
+        }
+        for (cik, reason) in &self.failed {
+            write!(f, "\n  CIK {} failed: {}", cik, reason)?;
+        }
+        Ok(())
+    }
+}
+
+impl Store {
+    const MAX_CONCURRENT_REQUESTS: usize = 8;
+
+    /// Filing date the next sync of the company starts from
+    pub fn sync_cursor(&self, cik: u32) -> Result<Option<NaiveDate>, FundamentalError> {
+        let last_filed: Option<String> = self
+            .conn
+            .query_row(
+                "SELECT last_filed FROM sync_cursor WHERE cik = ?1",
+                params![cik],
+                |row| row.get(0),
+            )
+            .optional()?;
+        Ok(last_filed.and_then(|date| date.parse().ok()))
+    }
+
+    fn set_sync_cursor(&self, cik: u32, last_filed: NaiveDate) -> Result<(), FundamentalError> {
+        self.conn.execute(
+            "INSERT INTO sync_cursor (cik, last_filed, synced_at) VALUES (?1, ?2, ?3)
+             ON CONFLICT (cik) DO UPDATE SET last_filed = ?2, synced_at = ?3",
+            params![cik, last_filed.to_string(), Utc::now().to_rfc3339()],
+        )?;
+        Ok(())
+    }
+
+    /// Bring the facts of every stored company up to date. Submissions are read for each
+    /// company by its stored CIK, so renamed, delisted and ticker-less companies are synced
+    /// too, and company facts are fetched again only when it has an original annual or
+    /// quarterly report whose accession number is not stored yet. Amendments often have no
+    /// XBRL facts, so they are only stored along with an original report and never held
+    /// pending. Filings made before the sync cursor are not looked at. The cursor moves to
+    /// the latest report whose facts are stored, but never past an original report still
+    /// missing from company facts.
+    pub async fn sync(&mut self, sec_client: &SecClient) -> Result<SyncSummary, FundamentalError> {
+        let mut summary = SyncSummary::default();
+        let ciks: Vec<u32> = self
+            .companies()?
+            .into_iter()
+            .map(|company| company.cik)
+            .collect();
+
+        let results = stream::iter(ciks)
+            .map(|cik| async move { (cik, sec_client.fetch_submissions_by_cik(cik).await) })
+            .buffer_unordered(Self::MAX_CONCURRENT_REQUESTS)
+            .collect::<Vec<_>>()
+            .await;
+
+        for (cik, res) in results {
+            summary.checked += 1;
+            if let Err(err) = self.sync_company(cik, sec_client, res, &mut summary).await {
+                warn!("Sync of CIK {} failed: {}", cik, err);
+                summary.failed.insert(cik, err.to_string());
+            }
+        }
+        Ok(summary)
+    }
+
+    async fn sync_company(
+        &mut self,
+        cik: u32,
+        client: &SecClient,
+        submissions: Result<Value, FundamentalError>,
+        summary: &mut SyncSummary,
+    ) -> Result<(), FundamentalError> {
+        let cursor = self.sync_cursor(cik)?;
+        let reports: Vec<Filing> = Self::recent_filings(&submissions?)?
+            .into_iter()
+            .filter(|filing| filing.form.is_annual() || filing.form.is_quarterly())
+            .filter(|filing| cursor.is_none_or(|cursor| filing.filed >= cursor))
+            .collect();
+        let mut stored = self.accessions(cik)?;
+        let new_filings: Vec<Filing> = reports
+            .iter()
+            .filter(|filing| !stored.contains(&filing.accn))
+            .cloned()
+            .collect();
+
+        if new_filings.iter().any(|filing| !filing.form.is_amendment()) {
+            debug!("CIK {} has {} new filings", cik, new_filings.len());
+            let json_data = client.fetch_data_by_cik(cik).await?;
+            self.upsert_company_facts(cik, &json_data)?;
+            stored = self.accessions(cik)?;
+            let (landed, missing): (Vec<Filing>, Vec<Filing>) = new_filings
+                .into_iter()
+                .partition(|filing| stored.contains(&filing.accn));
+            let pending: Vec<Filing> = missing
+                .into_iter()
+                .filter(|filing| !filing.form.is_amendment())
+                .collect();
+            if !landed.is_empty() {
+                summary.updated.insert(cik, landed);
+            }
+            if !pending.is_empty() {
+                summary.pending.insert(cik, pending);
+            }
+        }
+
+        let next_cursor = match summary.pending.get(&cik) {
+            Some(pending) => pending.iter().map(|filing| filing.filed).min(),
+            None => reports
+                .iter()
+                .filter(|filing| stored.contains(&filing.accn))
+                .map(|filing| filing.filed)
+                .max(),
+        };
+        match next_cursor.or(cursor) {
+            Some(next_cursor) => self.set_sync_cursor(cik, next_cursor),
+            None => Ok(()),
+        }
+    }
+
+    fn recent_filings(submissions: &Value) -> Result<Vec<Filing>, FundamentalError> {
+        let recent = RecentFilings::deserialize(&submissions["filings"]["recent"])?;
+        let filings = recent
+            .accession_number
+            .into_iter()
+            .zip(recent.form)
+            .zip(recent.filing_date)
+            .map(|((accn, form), filed)| Filing {
+                accn,
+                form: FormReport::from(form.as_str()),
+                filed,
+            })
+            .collect();
+        Ok(filings)
+    }
+}
@@ -0,0 +1,128 @@
+//! Property tests: parsing arbitrary companyfacts JSON must never panic.
+
+use fundamental::common::{HistoryMode, ParseMode};
+use fundamental::error::FundamentalError;
+use fundamental::financial_stmt::{
+    FinancialStatement, StatementHistory, balance_sheet::BalanceSheet, cash_flow::CashFlow,
+    income_statement::IncomeStatement,
+};
+use proptest::prelude::*;
+use serde_json::{Map, Value, json};
+
+const GAAP_TAGS: [&str; 9] = [
+    "Revenues",
+    "CostOfRevenue",
+    "NetIncomeLoss",
+    "OperatingIncomeLoss",
+    "Assets",
+    "LiabilitiesAndStockholdersEquity",
+    "StockholdersEquity",
+    "NetCashProvidedByUsedInOperatingActivities",
+    "CashCashEquivalentsRestrictedCashAndRestrictedCashEquivalents",
+];
+
+fn arb_json() -> impl Strategy<Value = Value> {
+    let leaf = prop_oneof![
+        Just(Value::Null),
+        any::<bool>().prop_map(Value::Bool),
+        any::<i64>().prop_map(Value::from),
+        any::<f64>().prop_map(Value::from),
+        ".{0,12}".prop_map(Value::String),
+    ];
+    leaf.prop_recursive(4, 32, 6, |inner| {
+        prop_oneof![
+            prop::collection::vec(inner.clone(), 0..6).prop_map(Value::Array),
+            prop::collection::btree_map(".{0,8}", inner, 0..6)
+                .prop_map(|map| Value::Object(map.into_iter().collect::<Map<_, _>>())),
+        ]
+    })
+}
+
+/// Either a plausible value for the field or anything at all
+fn field(plausible: impl Strategy<Value = Value> + 'static) -> BoxedStrategy<Value> {
+    prop_oneof![3 => plausible, 1 => arb_json()].boxed()
+}
+
+fn arb_date() -> impl Strategy<Value = Value> {
+    (1990i32..2035, 1u32..13, 1u32..29)
+        .prop_map(|(y, m, d)| Value::from(format!("{:04}-{:02}-{:02}", y, m, d)))
+}
+
+fn arb_fact() -> impl Strategy<Value = Value> {
+    (
+        field(arb_date()),
+        field(arb_date()),
+        field(any::<i64>().prop_map(Value::from)),
+        field(prop_oneof![Just("10-K"), Just("10-Q"), Just("8-K")].prop_map(Value::from)),
+        field(prop_oneof![Just("FY"), Just("Q1"), Just("Q2"), Just("Q3"), Just("Q4")].prop_map(Value::from)),
+        field((2000i64..2030).prop_map(Value::from)),
+    )
+        .prop_map(|(start, end, val, form, fp, fy)| {
+            json!({"start": start, "end": end, "val": val, "form": form, "fp": fp, "fy": fy, "accn": "0000000000-00-000000", "filed": "2025-01-01"})
+        })
+}
+
+fn arb_sec_json() -> impl Strategy<Value = Value> {
+    let concept = prop_oneof![
+        3 => prop::collection::vec(arb_fact(), 0..8).prop_map(|facts| json!({"units": {"USD": facts}})),
+        1 => arb_json().prop_map(|units| json!({"units": units})),
+    ];
+    prop::collection::vec((prop::sample::select(GAAP_TAGS.to_vec()), concept), 0..8).prop_map(
+        |concepts| {
+            let us_gaap: Map<String, Value> = concepts
+                .into_iter()
+                .map(|(tag, concept)| (tag.to_owned(), concept))
+                .collect();
+            json!({"facts": {"us-gaap": us_gaap}})
+        },
+    )
+}
+
+fn parse_all<T: FinancialStatement>(json_data: &Value) {
+    for parse_mode in [ParseMode::Strict, ParseMode::Tolerant] {
+        let mut statement = T::default();
+        statement.set_parse_mode(parse_mode);
+        let _ = statement.parse_quarly_latest(json_data);
+
+        let mut statement = T::default();
+        statement.set_parse_mode(parse_mode);
+        let _ = statement.parse_annually_latest(json_data);
+
+        for mode in [HistoryMode::Annual, HistoryMode::Quarterly] {
+            let mut history = StatementHistory::<T> {
+                parse_mode,
+                mode,
+                ..Default::default()
+            };
+            let _ = history.fill_history(json_data);
+        }
+    }
+}
+
+proptest! {
+    #[test]
+    fn test_arbitrary_json_never_panics(json_data in arb_json()) {
+        parse_all::<IncomeStatement>(&json_data);
+        parse_all::<BalanceSheet>(&json_data);
+        parse_all::<CashFlow>(&json_data);
+    }
+
+    #[test]
+    fn test_sec_shaped_json_never_panics(json_data in arb_sec_json()) {
+        parse_all::<IncomeStatement>(&json_data);
+        parse_all::<BalanceSheet>(&json_data);
+        parse_all::<CashFlow>(&json_data);
+    }
+
+    #[test]
+    fn test_tolerant_history_never_fails_on_facts(json_data in arb_sec_json()) {
+        let mut history = StatementHistory::<IncomeStatement> {
+            parse_mode: ParseMode::Tolerant,
+            ..Default::default()
+        };
+        // Only a missing tag or unit can fail, malformed facts are skipped
+        if let Err(err) = history.fill_history(&json_data) {
+            prop_assert!(!matches!(err, FundamentalError::MalformedFact { .. }), "{}", err);
+        }
+    }
+}
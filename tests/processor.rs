@@ -0,0 +1,38 @@
+mod support;
+
+use fundamental::financial_stmt::income_statement::IncomeStatement;
+use fundamental::processor::Processor;
+use support::MockEdgar;
+
+#[tokio::test]
+async fn test_map_company_by_industry() {
+    let server = MockEdgar::start().await;
+    let client = server.sec_client("");
+    let mut processor = Processor::default();
+    processor.map_company_by_industry(&client).await.unwrap();
+
+    let mapping = &processor.company_industry_mapping;
+    assert_eq!(mapping.len(), 3);
+    assert_eq!(mapping["3674"], vec![1045810]);
+    assert_eq!(mapping["3571"], vec![320193]);
+    assert_eq!(mapping["7372"], vec![789019]);
+}
+
+#[tokio::test]
+async fn test_collect_histories() {
+    let server = MockEdgar::start().await;
+    let client = server.sec_client("");
+    let mut processor = Processor::default();
+    processor.map_company_by_industry(&client).await.unwrap();
+
+    // Only NVIDIA has company facts on the mock server, the others are skipped
+    let histories = processor
+        .collect_histories::<IncomeStatement>(&client)
+        .await
+        .unwrap();
+    assert_eq!(histories.len(), 1);
+    assert_eq!(histories[0].cik, 1045810);
+    assert_eq!(histories[0].ticker.as_deref(), Some("NVDA"));
+    assert_eq!(histories[0].sic, "3674");
+    assert!(!histories[0].history.records.is_empty());
+}
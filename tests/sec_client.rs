@@ -0,0 +1,172 @@
+mod support;
+
+use std::time::Duration;
+
+use fundamental::error::FundamentalError;
+use fundamental::interface::{HttpClient, cache::HttpCache, retry::RetryPolicy};
+use support::{MockEdgar, MockResponse, fixture};
+
+const NVDA_FACTS: &str = "/api/xbrl/companyfacts/CIK0001045810.json";
+const TICKER_LOOKUP: &str = "/files/company_tickers_exchange.json";
+
+fn fast_retry_policy() -> RetryPolicy {
+    RetryPolicy {
+        base_delay: Duration::from_millis(10),
+        max_delay: Duration::from_millis(50),
+        ..RetryPolicy::default()
+    }
+}
+
+#[tokio::test]
+async fn test_fetch_data() {
+    let server = MockEdgar::start().await;
+    let client = server.sec_client("NVDA");
+    let json = client.fetch_data().await.unwrap();
+    assert_eq!(json["entityName"], "NVIDIA CORP");
+    assert!(json["facts"]["us-gaap"]["Revenues"].is_object());
+}
+
+#[tokio::test]
+async fn test_fetch_sic() {
+    let server = MockEdgar::start().await;
+    let client = server.sec_client("nvda");
+    let xml = client.fetch_sic().await.unwrap();
+    assert!(xml.contains("<assigned-sic>3674</assigned-sic>"));
+}
+
+#[tokio::test]
+async fn test_fetch_submissions() {
+    let server = MockEdgar::start().await;
+    let client = server.sec_client("NVDA");
+    let json = client.fetch_submissions().await.unwrap();
+    assert_eq!(json["sic"], "3674");
+    assert_eq!(json["filings"]["recent"]["form"][2], "10-K");
+}
+
+#[tokio::test]
+async fn test_fetch_by_cik() {
+    let server = MockEdgar::start().await;
+    // No ticker to look up
+    let client = server.sec_client("");
+    let json = client.fetch_submissions_by_cik(1045810).await.unwrap();
+    assert_eq!(json["sic"], "3674");
+    let json = client.fetch_data_by_cik(1045810).await.unwrap();
+    assert_eq!(json["entityName"], "NVIDIA CORP");
+    assert_eq!(server.hits(TICKER_LOOKUP), 0);
+}
+
+#[tokio::test]
+async fn test_ticker_directory_loaded_once() {
+    let server = MockEdgar::start().await;
+    let mut client = server.sec_client("NVDA");
+    client.fetch_sic().await.unwrap();
+    client.set_ticker(String::from("AAPL"));
+    client.fetch_sic().await.unwrap();
+    client.clone().fetch_data().await.ok();
+    assert_eq!(server.hits(TICKER_LOOKUP), 1);
+}
+
+#[tokio::test]
+async fn test_retry_on_service_unavailable() {
+    let server = MockEdgar::start().await;
+    server.mount_sequence(
+        NVDA_FACTS,
+        vec![
+            MockResponse::status(503),
+            MockResponse::status(429).with_header("Retry-After", "0"),
+            MockResponse::json(fixture("companyfacts_CIK0001045810.json")),
+        ],
+    );
+    let mut client = server.sec_client("NVDA");
+    client.set_retry_policy(fast_retry_policy());
+    let json = client.fetch_data().await.unwrap();
+    assert_eq!(json["cik"], 1045810);
+    assert_eq!(server.hits(NVDA_FACTS), 3);
+}
+
+#[tokio::test]
+async fn test_retry_gives_up() {
+    let server = MockEdgar::start().await;
+    server.mount(NVDA_FACTS, MockResponse::status(503));
+    let mut client = server.sec_client("NVDA");
+    client.set_retry_policy(RetryPolicy {
+        max_attempts: 2,
+        ..fast_retry_policy()
+    });
+    assert!(matches!(
+        client.fetch_data().await,
+        Err(FundamentalError::Http(_))
+    ));
+    assert_eq!(server.hits(NVDA_FACTS), 2);
+}
+
+#[tokio::test]
+async fn test_rate_limited() {
+    let server = MockEdgar::start().await;
+    server.mount(
+        NVDA_FACTS,
+        MockResponse::status(429).with_header("Retry-After", "0"),
+    );
+    let mut client = server.sec_client("NVDA");
+    client.set_retry_policy(RetryPolicy::no_retry());
+    assert!(matches!(
+        client.fetch_data().await,
+        Err(FundamentalError::RateLimited { .. })
+    ));
+}
+
+#[tokio::test]
+async fn test_ticker_not_found() {
+    let server = MockEdgar::start().await;
+    let client = server.sec_client("UNKNOWN");
+    let err = client.fetch_data().await.unwrap_err();
+    assert!(matches!(err, FundamentalError::TickerNotFound { ticker } if ticker == "UNKNOWN"));
+    assert_eq!(server.hits(NVDA_FACTS), 0);
+}
+
+#[tokio::test]
+async fn test_cache_revalidates_with_etag() {
+    let server = MockEdgar::start().await;
+    server.mount_sequence(
+        NVDA_FACTS,
+        vec![
+            MockResponse::json(fixture("companyfacts_CIK0001045810.json"))
+                .with_header("ETag", "\"v1\""),
+            MockResponse::status(304),
+        ],
+    );
+    let cache_dir = tempfile::tempdir().unwrap();
+    let mut client = server.sec_client("NVDA");
+    client.set_cache(HttpCache::new(cache_dir.path()));
+
+    let first = client.fetch_data().await.unwrap();
+    let second = client.fetch_data().await.unwrap();
+    assert_eq!(first, second);
+    assert_eq!(server.hits(NVDA_FACTS), 2);
+    let headers = server.last_request_headers(NVDA_FACTS).unwrap();
+    assert!(headers.to_lowercase().contains("if-none-match: \"v1\""));
+}
+
+#[tokio::test]
+async fn test_cache_offline_only() {
+    let server = MockEdgar::start().await;
+    let cache_dir = tempfile::tempdir().unwrap();
+    let mut client = server.sec_client("NVDA");
+    client.set_cache(HttpCache::new(cache_dir.path()));
+    let online = client.fetch_data().await.unwrap();
+
+    let mut offline = client.clone();
+    offline.set_cache(HttpCache {
+        offline_only: true,
+        ..HttpCache::new(cache_dir.path())
+    });
+    let cached = offline.fetch_data().await.unwrap();
+    assert_eq!(online, cached);
+    assert_eq!(server.hits(NVDA_FACTS), 1);
+
+    offline.set_ticker(String::from("AAPL"));
+    assert!(matches!(
+        offline.fetch_data().await,
+        Err(FundamentalError::NotCached { url }) if url.ends_with("CIK0000320193.json")
+    ));
+}
@@ -0,0 +1,207 @@
+//! Local mock EDGAR server serving canned fixtures for integration tests.
+
+#![allow(dead_code)]
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+
+use fundamental::financial_stmt::sec_client::{ConfiguredHttpClient, SecClient, SecEndpoints};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+pub const FIXTURES_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures");
+
+pub fn fixture(name: &str) -> String {
+    std::fs::read_to_string(format!("{}/{}", FIXTURES_DIR, name))
+        .unwrap_or_else(|err| panic!("missing fixture {}: {}", name, err))
+}
+
+#[derive(Debug, Clone)]
+pub struct MockResponse {
+    pub status: u16,
+    pub content_type: &'static str,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+impl MockResponse {
+    pub fn json(body: String) -> Self {
+        Self::new(200, "application/json", body)
+    }
+
+    pub fn xml(body: String) -> Self {
+        Self::new(200, "application/atom+xml", body)
+    }
+
+    pub fn status(status: u16) -> Self {
+        Self::new(status, "text/plain", String::new())
+    }
+
+    pub fn new(status: u16, content_type: &'static str, body: String) -> Self {
+        Self {
+            status,
+            content_type,
+            headers: Vec::new(),
+            body,
+        }
+    }
+
+    pub fn with_header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_owned(), value.to_owned()));
+        self
+    }
+}
+
+#[derive(Debug, Default)]
+struct State {
+    /// Responses per request target (path and query). The last one is repeated forever.
+    routes: HashMap<String, VecDeque<MockResponse>>,
+    /// Every request received: (target, raw request headers)
+    requests: Vec<(String, String)>,
+}
+
+/// Minimal HTTP/1.1 server laid out like `www.sec.gov` and `data.sec.gov`
+#[derive(Clone)]
+pub struct MockEdgar {
+    pub base_url: String,
+    state: Arc<Mutex<State>>,
+}
+
+impl MockEdgar {
+    /// Start a server with the canned EDGAR fixtures mounted
+    pub async fn start() -> Self {
+        let server = Self::start_empty().await;
+        server.mount(
+            "/files/company_tickers_exchange.json",
+            MockResponse::json(fixture("company_tickers_exchange.json")),
+        );
+        server.mount(
+            "/files/company_tickers.json",
+            MockResponse::json(fixture("company_tickers.json")),
+        );
+        server.mount(
+            "/api/xbrl/companyfacts/CIK0001045810.json",
+            MockResponse::json(fixture("companyfacts_CIK0001045810.json")),
+        );
+        server.mount(
+            "/submissions/CIK0001045810.json",
+            MockResponse::json(fixture("submissions_CIK0001045810.json")),
+        );
+        for cik in ["0001045810", "0000320193", "0000789019"] {
+            server.mount(
+                &format!(
+                    "/cgi-bin/browse-edgar?action=getcompany&output=atom&CIK={}",
+                    cik
+                ),
+                MockResponse::xml(fixture(&format!("browse_edgar_CIK{}.atom", cik))),
+            );
+        }
+        server
+    }
+
+    pub async fn start_empty() -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        let state = Arc::new(Mutex::new(State::default()));
+        let accept_state = Arc::clone(&state);
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                tokio::spawn(Self::handle(stream, Arc::clone(&accept_state)));
+            }
+        });
+        Self { base_url, state }
+    }
+
+    /// Serve `response` for `target`, replacing any previous responses
+    pub fn mount(&self, target: &str, response: MockResponse) {
+        self.mount_sequence(target, vec![response]);
+    }
+
+    /// Serve `responses` in order for `target`, repeating the last one
+    pub fn mount_sequence(&self, target: &str, responses: Vec<MockResponse>) {
+        let mut state = self.state.lock().unwrap();
+        state.routes.insert(target.to_owned(), responses.into());
+    }
+
+    /// Number of requests received for `target`
+    pub fn hits(&self, target: &str) -> usize {
+        let state = self.state.lock().unwrap();
+        state.requests.iter().filter(|(t, _)| t == target).count()
+    }
+
+    /// Raw headers of the last request received for `target`
+    pub fn last_request_headers(&self, target: &str) -> Option<String> {
+        let state = self.state.lock().unwrap();
+        state
+            .requests
+            .iter()
+            .rev()
+            .find(|(t, _)| t == target)
+            .map(|(_, headers)| headers.clone())
+    }
+
+    pub fn endpoints(&self) -> SecEndpoints {
+        SecEndpoints::with_base_url(&self.base_url)
+    }
+
+    /// SecClient pointed at this server
+    pub fn sec_client(&self, ticker: &str) -> SecClient {
+        let mut client = SecClient::new(ticker.to_owned(), ConfiguredHttpClient::new().unwrap());
+        client.set_endpoints(self.endpoints());
+        client
+    }
+
+    async fn handle(mut stream: TcpStream, state: Arc<Mutex<State>>) {
+        let mut raw = Vec::new();
+        let mut buf = [0u8; 4096];
+        while !raw.windows(4).any(|w| w == b"\r\n\r\n") {
+            match stream.read(&mut buf).await {
+                Ok(0) | Err(_) => return,
+                Ok(n) => raw.extend_from_slice(&buf[..n]),
+            }
+        }
+        let request = String::from_utf8_lossy(&raw).into_owned();
+        let target = request
+            .lines()
+            .next()
+            .and_then(|line| line.split_whitespace().nth(1))
+            .unwrap_or("/")
+            .to_owned();
+
+        let response = {
+            let mut state = state.lock().unwrap();
+            state.requests.push((target.clone(), request));
+            match state.routes.get_mut(&target) {
+                Some(queue) if queue.len() > 1 => queue.pop_front().unwrap(),
+                Some(queue) => queue.front().cloned().unwrap(),
+                None => MockResponse::status(404),
+            }
+        };
+
+        let mut head = format!(
+            "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n",
+            response.status,
+            Self::reason(response.status),
+            response.content_type,
+            response.body.len()
+        );
+        for (name, value) in &response.headers {
+            head.push_str(&format!("{}: {}\r\n", name, value));
+        }
+        head.push_str("\r\n");
+        let _ = stream.write_all(head.as_bytes()).await;
+        let _ = stream.write_all(response.body.as_bytes()).await;
+        let _ = stream.shutdown().await;
+    }
+
+    fn reason(status: u16) -> &'static str {
+        match status {
+            200 => "OK",
+            304 => "Not Modified",
+            404 => "Not Found",
+            429 => "Too Many Requests",
+            503 => "Service Unavailable",
+            _ => "Unknown",
+        }
+    }
+}
@@ -0,0 +1,182 @@
+mod support;
+
+use chrono::NaiveDate;
+use fundamental::financial_stmt::sec_client::CompanyTickersExchange;
+use fundamental::store::Store;
+use serde_json::{Value, json};
+use support::{MockEdgar, MockResponse, fixture};
+
+const NVDA_FACTS: &str = "/api/xbrl/companyfacts/CIK0001045810.json";
+const NVDA_SUBMISSIONS: &str = "/submissions/CIK0001045810.json";
+
+fn company(cik: u32, ticker: Option<&str>) -> CompanyTickersExchange {
+    CompanyTickersExchange {
+        cik,
+        name: None,
+        ticker: ticker.map(str::to_owned),
+        exchange: None,
+    }
+}
+
+fn date(value: &str) -> NaiveDate {
+    value.parse().unwrap()
+}
+
+/// NVIDIA submissions with `(accn, filed, form)` filings added as the most recent ones
+fn submissions_with(filings: &[(&str, &str, &str)]) -> MockResponse {
+    let mut submissions: Value =
+        serde_json::from_str(&fixture("submissions_CIK0001045810.json")).unwrap();
+    let recent = &mut submissions["filings"]["recent"];
+    for (accn, filed, form) in filings {
+        for (column, value) in [
+            ("accessionNumber", *accn),
+            ("filingDate", *filed),
+            ("reportDate", ""),
+            ("form", *form),
+            ("primaryDocument", ""),
+        ] {
+            recent[column]
+                .as_array_mut()
+                .unwrap()
+                .insert(0, json!(value));
+        }
+    }
+    MockResponse::json(submissions.to_string())
+}
+
+#[tokio::test]
+async fn test_sync_fetches_only_new_filings() {
+    let server = MockEdgar::start().await;
+    let client = server.sec_client("");
+    let mut store = Store::open_in_memory().unwrap();
+    // Synced by CIK, a ticker gone from the directory does not matter
+    store
+        .upsert_company(&company(1045810, Some("NVDA.OLD")), None)
+        .unwrap();
+    // No submissions on the mock server
+    store.upsert_company(&company(789019, None), None).unwrap();
+
+    let summary = store.sync(&client).await.unwrap();
+    assert_eq!(summary.checked, 2);
+    assert_eq!(summary.updated[&1045810].len(), 9);
+    assert!(summary.failed.contains_key(&789019));
+    assert_eq!(server.hits(NVDA_FACTS), 1);
+    // Latest report whose facts are stored, not the later 8-K and Form 4
+    assert_eq!(
+        store.sync_cursor(1045810).unwrap(),
+        Some(date("2025-02-18"))
+    );
+    assert_eq!(store.accessions(1045810).unwrap().len(), 9);
+
+    // Nothing new, the 8-K and Form 4 never trigger a fetch
+    let summary = store.sync(&client).await.unwrap();
+    assert!(summary.updated.is_empty());
+    assert_eq!(server.hits(NVDA_FACTS), 1);
+
+    // A new 10-Q triggers a fetch, followed by an 8-K
+    server.mount(
+        NVDA_SUBMISSIONS,
+        submissions_with(&[
+            ("0001045810-25-000020", "2025-05-28", "10-Q"),
+            ("0001045810-25-000022", "2025-06-02", "8-K"),
+        ]),
+    );
+
+    // Company facts lag submissions, the 10-Q stays pending until its facts land
+    for hits in [2, 3] {
+        let summary = store.sync(&client).await.unwrap();
+        assert!(summary.updated.is_empty());
+        assert_eq!(summary.pending[&1045810][0].accn, "0001045810-25-000020");
+        assert_eq!(server.hits(NVDA_FACTS), hits);
+        assert_eq!(
+            store.sync_cursor(1045810).unwrap(),
+            Some(date("2025-05-28"))
+        );
+    }
+    assert!(store.sync(&client).await.unwrap().to_string().contains(
+        "CIK 1045810: 10-Q 0001045810-25-000020 filed 2025-05-28 not in company facts yet"
+    ));
+
+    let mut facts: Value =
+        serde_json::from_str(&fixture("companyfacts_CIK0001045810.json")).unwrap();
+    facts["facts"]["us-gaap"]["Revenues"]["units"]["USD"]
+        .as_array_mut()
+        .unwrap()
+        .push(json!({"start": "2025-01-01", "end": "2025-03-31", "val": 180000000, "accn": "0001045810-25-000020", "fy": 2025, "fp": "Q1", "form": "10-Q", "filed": "2025-05-28"}));
+    server.mount(NVDA_FACTS, MockResponse::json(facts.to_string()));
+
+    let summary = store.sync(&client).await.unwrap();
+    let filings = &summary.updated[&1045810];
+    assert_eq!(filings.len(), 1);
+    assert_eq!(filings[0].accn, "0001045810-25-000020");
+    assert!(summary.pending.is_empty());
+    assert_eq!(server.hits(NVDA_FACTS), 5);
+    assert_eq!(store.accessions(1045810).unwrap().len(), 10);
+    assert_eq!(
+        store.sync_cursor(1045810).unwrap(),
+        Some(date("2025-05-28"))
+    );
+    assert!(
+        summary
+            .to_string()
+            .starts_with("Checked 2 companies: 1 updated, 0 pending, 1 failed")
+    );
+    assert!(
+        summary
+            .to_string()
+            .contains("CIK 1045810: 10-Q 0001045810-25-000020 filed 2025-05-28")
+    );
+}
+
+#[tokio::test]
+async fn test_sync_skips_amendments_missing_from_facts() {
+    let server = MockEdgar::start().await;
+    let client = server.sec_client("");
+    let mut store = Store::open_in_memory().unwrap();
+    store
+        .upsert_company(&company(1045810, Some("NVDA")), None)
+        .unwrap();
+    store.sync(&client).await.unwrap();
+    assert_eq!(server.hits(NVDA_FACTS), 1);
+
+    // A 10-K/A without XBRL facts never lands in company facts and is not waited for
+    server.mount(
+        NVDA_SUBMISSIONS,
+        submissions_with(&[("0001045810-25-000010", "2025-03-14", "10-K/A")]),
+    );
+    for _ in 0..2 {
+        let summary = store.sync(&client).await.unwrap();
+        assert!(summary.updated.is_empty() && summary.pending.is_empty());
+        assert_eq!(server.hits(NVDA_FACTS), 1);
+        assert_eq!(
+            store.sync_cursor(1045810).unwrap(),
+            Some(date("2025-02-18"))
+        );
+    }
+
+    // A later 10-Q is fetched and moves the cursor past the amendment
+    server.mount(
+        NVDA_SUBMISSIONS,
+        submissions_with(&[
+            ("0001045810-25-000010", "2025-03-14", "10-K/A"),
+            ("0001045810-25-000020", "2025-05-28", "10-Q"),
+        ]),
+    );
+    let mut facts: Value =
+        serde_json::from_str(&fixture("companyfacts_CIK0001045810.json")).unwrap();
+    facts["facts"]["us-gaap"]["Revenues"]["units"]["USD"]
+        .as_array_mut()
+        .unwrap()
+        .push(json!({"start": "2025-01-01", "end": "2025-03-31", "val": 180000000, "accn": "0001045810-25-000020", "fy": 2025, "fp": "Q1", "form": "10-Q", "filed": "2025-05-28"}));
+    server.mount(NVDA_FACTS, MockResponse::json(facts.to_string()));
+    let summary = store.sync(&client).await.unwrap();
+    assert_eq!(summary.updated[&1045810].len(), 1);
+    assert!(summary.pending.is_empty());
+    assert_eq!(server.hits(NVDA_FACTS), 2);
+    assert_eq!(
+        store.sync_cursor(1045810).unwrap(),
+        Some(date("2025-05-28"))
+    );
+    store.sync(&client).await.unwrap();
+    assert_eq!(server.hits(NVDA_FACTS), 2);
+}